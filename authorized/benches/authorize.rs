@@ -0,0 +1,114 @@
+//! Compare the cost of authorizing with a string scope (parsed on every call) and with a
+//! pre-parsed scope, and the cost of authorizing with the cost of only listing the unauthorized
+//! fields.
+//!
+//! Run with `cargo bench -p authorized`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use authorized::prelude::*;
+
+const ITERATIONS: u32 = 1_000_000;
+
+#[derive(Debug, Authorized)]
+#[authorized(scope = "read:user")]
+struct User {
+    id: i32,
+    name: String,
+    #[authorized(scope = "admin")]
+    email: String,
+}
+
+impl Authorized for User {}
+
+#[derive(Debug, Default, Authorized)]
+struct Wide {
+    f00: String,
+    f01: String,
+    f02: String,
+    f03: String,
+    f04: String,
+    f05: String,
+    f06: String,
+    f07: String,
+    f08: String,
+    f09: String,
+    #[authorized(scope = "admin")]
+    f10: String,
+    #[authorized(scope = "admin")]
+    f11: String,
+    #[authorized(scope = "admin")]
+    f12: String,
+    #[authorized(scope = "admin")]
+    f13: String,
+    #[authorized(scope = "admin")]
+    f14: String,
+    #[authorized(scope = "billing")]
+    f15: String,
+    #[authorized(scope = "billing")]
+    f16: String,
+    #[authorized(scope = "billing")]
+    f17: String,
+    #[authorized(scope = "billing")]
+    f18: String,
+    #[authorized(scope = "billing")]
+    f19: String,
+}
+
+impl Authorized for Wide {}
+
+fn bench<F: FnMut()>(name: &str, mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "{:<16} {:>10.2?} total, {:>8.2?}/iter",
+        name,
+        elapsed,
+        elapsed / ITERATIONS
+    );
+
+    elapsed
+}
+
+fn main() {
+    let user = User {
+        id: 1,
+        name: "name".into(),
+        email: "email".into(),
+    };
+    let scope = "read:user admin".parse::<Scope>().unwrap();
+
+    let string = bench("authorize", || {
+        black_box(Authorizor::authorize(black_box(&user), &"read:user admin").unwrap());
+    });
+    let parsed = bench("authorize_ref", || {
+        black_box(Authorizor::authorize_ref(black_box(&user), black_box(&scope)).unwrap());
+    });
+
+    println!(
+        "authorize_ref is {:.2}x faster",
+        string.as_secs_f64() / parsed.as_secs_f64()
+    );
+
+    let wide = Wide::default();
+    let scope = "admin".parse::<Scope>().unwrap();
+
+    let authorized = bench("authorize (20)", || {
+        black_box(Authorizor::authorize(black_box(&wide), black_box(&scope)).unwrap());
+    });
+    let fields = bench("fields_for (20)", || {
+        black_box(
+            Authorizor::unauthorized_fields_for(black_box(&wide), black_box(&scope)).unwrap(),
+        );
+    });
+
+    println!(
+        "unauthorized_fields_for is {:.2}x faster",
+        authorized.as_secs_f64() / fields.as_secs_f64()
+    );
+}
@@ -0,0 +1,229 @@
+//! `actix-web` integration.
+//!
+//! [`ScopeMiddleware`](struct.ScopeMiddleware.html) parses the `Authorization: Bearer <scope>`
+//! header once per request and stores the resulting [`Scope`](../scope/struct.Scope.html) in
+//! the request extensions. Handlers can then get it back with the
+//! [`ScopeGuard`](struct.ScopeGuard.html) extractor.
+//!
+//! ```no_run
+//! use actix_web::{web, App, HttpServer};
+//! use authorized::actix::{ScopeGuard, ScopeMiddleware};
+//!
+//! async fn index(ScopeGuard(scope): ScopeGuard) -> String {
+//!     format!("{:?}", scope)
+//! }
+//!
+//! # fn main() -> std::io::Result<()> {
+//! # actix_web::rt::System::new().block_on(async {
+//! HttpServer::new(|| {
+//!     App::new()
+//!         .wrap(ScopeMiddleware::new())
+//!         .route("/", web::get().to(index))
+//! })
+//! .bind(("127.0.0.1", 8080))?
+//! .run()
+//! .await
+//! # })
+//! # }
+//! ```
+
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::ErrorUnauthorized;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+
+use crate::scope::Scope;
+
+/// Middleware extracting a [`Scope`](../scope/struct.Scope.html) from the `Authorization`
+/// header and inserting it in the request extensions.
+///
+/// When the header is missing or can't be parsed, the request is rejected with a
+/// `401 Unauthorized` unless a fallback scope has been defined.
+#[derive(Clone, Debug, Default)]
+pub struct ScopeMiddleware {
+    fallback: Option<Scope>,
+}
+
+impl ScopeMiddleware {
+    /// Create a middleware rejecting every request without a valid bearer scope.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { fallback: None }
+    }
+
+    /// Create a middleware using `fallback` when the request doesn't provide a valid bearer
+    /// scope.
+    #[must_use]
+    pub fn with_fallback(fallback: Scope) -> Self {
+        Self {
+            fallback: Some(fallback),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ScopeMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ScopeMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ScopeMiddlewareService {
+            service,
+            fallback: self.fallback.clone(),
+        }))
+    }
+}
+
+/// Service created by [`ScopeMiddleware`](struct.ScopeMiddleware.html).
+pub struct ScopeMiddlewareService<S> {
+    service: S,
+    fallback: Option<Scope>,
+}
+
+impl<S, B> Service<ServiceRequest> for ScopeMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let scope = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Scope::from_bearer)
+            .or_else(|| self.fallback.clone());
+
+        if let Some(scope) = scope {
+            req.extensions_mut().insert(scope);
+
+            Box::pin(self.service.call(req))
+        } else {
+            Box::pin(ready(Err(ErrorUnauthorized(
+                "Missing or invalid bearer scope",
+            ))))
+        }
+    }
+}
+
+/// Extractor giving access to the [`Scope`](../scope/struct.Scope.html) stored by
+/// [`ScopeMiddleware`](struct.ScopeMiddleware.html).
+///
+/// Extraction fails with a `401 Unauthorized` if no scope has been stored for the request.
+#[derive(Clone, Debug)]
+pub struct ScopeGuard(pub Scope);
+
+impl FromRequest for ScopeGuard {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Scope>()
+                .cloned()
+                .map(ScopeGuard)
+                .ok_or_else(|| ErrorUnauthorized("No scope found for this request")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+
+    async fn handler(ScopeGuard(scope): ScopeGuard) -> HttpResponse {
+        if "admin".parse::<Scope>().unwrap().allow_access(&scope) {
+            HttpResponse::Ok().finish()
+        } else {
+            HttpResponse::Forbidden().finish()
+        }
+    }
+
+    #[test]
+    fn middleware_stores_bearer_scope() {
+        actix_web::rt::System::new().block_on(async {
+            let app = init_service(
+                App::new()
+                    .wrap(ScopeMiddleware::new())
+                    .route("/", web::get().to(handler)),
+            )
+            .await;
+
+            let req = TestRequest::get()
+                .uri("/")
+                .insert_header((AUTHORIZATION, "Bearer admin read:user"))
+                .to_request();
+            assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+            let req = TestRequest::get()
+                .uri("/")
+                .insert_header((AUTHORIZATION, "bearer admin"))
+                .to_request();
+            assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+            let req = TestRequest::get()
+                .uri("/")
+                .insert_header((AUTHORIZATION, "Bearer read:user"))
+                .to_request();
+            assert_eq!(
+                call_service(&app, req).await.status(),
+                StatusCode::FORBIDDEN
+            );
+        });
+    }
+
+    #[test]
+    fn middleware_rejects_or_falls_back() {
+        actix_web::rt::System::new().block_on(async {
+            let app = init_service(
+                App::new()
+                    .wrap(ScopeMiddleware::new())
+                    .route("/", web::get().to(handler)),
+            )
+            .await;
+
+            let req = TestRequest::get().uri("/").to_request();
+            let err = app.call(req).await.err().unwrap();
+            assert_eq!(
+                err.as_response_error().status_code(),
+                StatusCode::UNAUTHORIZED
+            );
+
+            let app = init_service(
+                App::new()
+                    .wrap(ScopeMiddleware::with_fallback(
+                        "admin".parse::<Scope>().unwrap(),
+                    ))
+                    .route("/", web::get().to(handler)),
+            )
+            .await;
+
+            let req = TestRequest::get()
+                .uri("/")
+                .insert_header((AUTHORIZATION, "Bearer \"invalid\""))
+                .to_request();
+            assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+        });
+    }
+}
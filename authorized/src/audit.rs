@@ -0,0 +1,318 @@
+//! Per-field authorization audit.
+//!
+//! An audit callback can be registered with
+//! [`Authorizor::with_field_audit`](../struct.Authorizor.html#method.with_field_audit). While
+//! [`AuditedAuthorizor::authorize_audited`](struct.AuditedAuthorizor.html#method.authorize_audited)
+//! runs, the callback is stored in a thread local. The derived `filter_unauthorized_fields`
+//! fetches it once and calls it for every field decision, so the callback doesn't need to be
+//! threaded through the [`Authorizable`](../trait.Authorizable.html) methods.
+//!
+//! A [`FieldAuditTrail`](struct.FieldAuditTrail.html) records the unauthorized fields of a
+//! resource authorized several times, e.g. with a read scope then with a write scope.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::error::AuthorizedError;
+use crate::result::AuthorizedResult;
+use crate::scope::{IntoScope, Scope};
+use crate::{Authorizable, Authorizor, UnAuthorizedFields};
+
+type FieldAuditCallback = Rc<dyn Fn(FieldAuditEvent)>;
+
+thread_local! {
+    static AUDIT_CALLBACK: RefCell<Option<FieldAuditCallback>> = RefCell::new(None);
+}
+
+/// Decision taken for a single field during an authorization.
+#[derive(Clone, Debug)]
+pub struct FieldAuditEvent {
+    pub field_name: &'static str,
+    pub struct_type: &'static str,
+    pub access_granted: bool,
+    /// The scope the field requirement has been checked against.
+    pub scope_checked: Scope,
+    pub timestamp: SystemTime,
+}
+
+/// Authorizor reporting field decisions to a callback, created by
+/// [`Authorizor::with_field_audit`](../struct.Authorizor.html#method.with_field_audit).
+pub struct AuditedAuthorizor {
+    callback: FieldAuditCallback,
+}
+
+impl AuditedAuthorizor {
+    pub(crate) fn new(callback: FieldAuditCallback) -> Self {
+        Self { callback }
+    }
+
+    /// Same as [`Authorizor::authorize`](../struct.Authorizor.html#method.authorize) but reports
+    /// every field decision to the registered callback.
+    ///
+    /// # Errors
+    ///
+    pub fn authorize_audited<A: Authorizable, T: IntoScope>(
+        &self,
+        inner: &A,
+        scope: &T,
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        let _guard = AuditGuard::register(Some(Rc::clone(&self.callback)));
+
+        Authorizor::authorize(inner, scope)
+    }
+}
+
+/// Keeps `callback` registered for the current thread until dropped, then restores the
+/// previously registered callback.
+struct AuditGuard {
+    previous: Option<FieldAuditCallback>,
+}
+
+impl AuditGuard {
+    fn register(callback: Option<FieldAuditCallback>) -> Self {
+        let previous = AUDIT_CALLBACK.with(|cell| cell.replace(callback));
+
+        Self { previous }
+    }
+}
+
+impl Drop for AuditGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        AUDIT_CALLBACK.with(|cell| cell.replace(previous));
+    }
+}
+
+/// The callback registered for the current thread, fetched once per
+/// `filter_unauthorized_fields` call.
+///
+/// This is used by the code generated by `#[derive(Authorized)]`.
+#[doc(hidden)]
+pub struct FieldAudit(Option<FieldAuditCallback>);
+
+impl FieldAudit {
+    #[must_use]
+    pub fn current() -> Self {
+        Self(AUDIT_CALLBACK.with(|cell| cell.borrow().clone()))
+    }
+
+    /// Report a field decision to the registered callback, if any.
+    pub fn emit(
+        &self,
+        field_name: &'static str,
+        struct_type: &'static str,
+        access_granted: bool,
+        scope_checked: &Scope,
+    ) {
+        if let Some(callback) = &self.0 {
+            callback(FieldAuditEvent {
+                field_name,
+                struct_type,
+                access_granted,
+                scope_checked: scope_checked.clone(),
+                timestamp: SystemTime::now(),
+            });
+        }
+    }
+}
+
+/// The unauthorized fields of a resource for one scope, recorded in a
+/// [`FieldAuditTrail`](struct.FieldAuditTrail.html).
+#[derive(Clone, Debug)]
+pub struct FieldAuditDecision {
+    pub scope: Scope,
+    pub unauthorized_fields: UnAuthorizedFields,
+    pub timestamp: SystemTime,
+}
+
+/// Authorization decisions taken on the same resource, in the order they were recorded.
+#[derive(Clone, Debug, Default)]
+pub struct FieldAuditTrail {
+    pub decisions: Vec<FieldAuditDecision>,
+}
+
+impl FieldAuditTrail {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the fields of `input` which are unauthorized for `scope`. The fields of the items
+    /// of a collection are prefixed by their index, e.g. `0.email`.
+    pub fn record<T: Authorizable>(&mut self, input: &T, scope: &Scope) {
+        self.decisions.push(FieldAuditDecision {
+            scope: scope.clone(),
+            unauthorized_fields: T::filter_unauthorized_fields(input, scope),
+            timestamp: SystemTime::now(),
+        });
+    }
+
+    /// The scopes which denied each field, by field name.
+    #[must_use]
+    pub fn summary(&self) -> HashMap<String, Vec<Scope>> {
+        let mut summary: HashMap<String, Vec<Scope>> = HashMap::new();
+
+        for decision in &self.decisions {
+            for field in &decision.unauthorized_fields {
+                summary
+                    .entry(field.clone())
+                    .or_default()
+                    .push(decision.scope.clone());
+            }
+        }
+
+        summary
+    }
+}
+
+/// One line per decision, with the scope and the unauthorized fields.
+impl fmt::Display for FieldAuditTrail {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for (i, decision) in self.decisions.iter().enumerate() {
+            if i > 0 {
+                writeln!(fmt)?;
+            }
+
+            if decision.unauthorized_fields.is_empty() {
+                write!(fmt, "`{}`: every field authorized", decision.scope)?;
+            } else {
+                write!(
+                    fmt,
+                    "`{}`: unauthorized fields {}",
+                    decision.scope,
+                    decision.unauthorized_fields.join(", ")
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Authorized)]
+    struct Customer {
+        id: i32,
+        #[authorized(scope = "admin")]
+        email: String,
+    }
+
+    impl Authorized for Customer {}
+
+    #[test]
+    fn callback_fires_once_per_field_per_call() -> Result<(), AuthorizedError> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&events);
+
+        let customer = Customer {
+            id: 1,
+            email: "email".into(),
+        };
+
+        let authorizor =
+            Authorizor {}.with_field_audit(move |event| recorded.borrow_mut().push(event));
+
+        authorizor.authorize_audited(&customer, &"user")?;
+        assert_eq!(events.borrow().len(), 2);
+
+        let email = events.borrow()[1].clone();
+        assert_eq!(email.field_name, "email");
+        assert_eq!(email.struct_type, "Customer");
+        assert!(!email.access_granted);
+        assert_eq!(email.scope_checked, "user".parse::<Scope>().unwrap());
+        assert!(events.borrow()[0].access_granted);
+
+        authorizor.authorize_audited(&customer, &"admin")?;
+        assert_eq!(events.borrow().len(), 4);
+        assert!(events.borrow()[3].access_granted);
+
+        // Once the audited call is over the callback is unregistered.
+        Authorizor::authorize(&customer, &"admin")?;
+        assert_eq!(events.borrow().len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn grouped_fields_are_audited_once() -> Result<(), AuthorizedError> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&events);
+
+        let customer = Customer {
+            id: 1,
+            email: "email".into(),
+        };
+
+        let mut field_scopes = HashMap::new();
+        field_scopes.insert("email", "user".parse::<Scope>()?);
+
+        let _guard = AuditGuard::register(Some(Rc::new(move |event| {
+            recorded.borrow_mut().push(event);
+        })));
+        let res = Authorizor::authorize_group(&customer, &field_scopes)?;
+        assert_eq!(res.unauthorized_fields, vec!["email"]);
+
+        // Only the listed field is checked, once.
+        assert_eq!(events.borrow().len(), 1);
+        assert_eq!(events.borrow()[0].field_name, "email");
+        assert!(!events.borrow()[0].access_granted);
+
+        Ok(())
+    }
+
+    #[test]
+    fn trail_records_every_decision() {
+        let customer = Customer {
+            id: 1,
+            email: "email".into(),
+        };
+        let read = "read".parse::<Scope>().unwrap();
+        let admin = "admin".parse::<Scope>().unwrap();
+
+        let mut trail = FieldAuditTrail::new();
+        trail.record(&customer, &read);
+        trail.record(&customer, &admin);
+        trail.record(&customer, &read);
+
+        assert_eq!(trail.decisions.len(), 3);
+        assert_eq!(trail.decisions[0].unauthorized_fields, vec!["email"]);
+        assert!(trail.decisions[1].unauthorized_fields.is_empty());
+
+        let summary = trail.summary();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary["email"], vec![read.clone(), read]);
+
+        assert_eq!(
+            trail.to_string(),
+            "`read`: unauthorized fields email\n`admin`: every field authorized\n`read`: unauthorized fields email"
+        );
+    }
+
+    #[test]
+    fn trail_records_references_and_collections() {
+        let customer = || Customer {
+            id: 1,
+            email: "email".into(),
+        };
+        let read = "read".parse::<Scope>().unwrap();
+
+        let mut trail = FieldAuditTrail::new();
+        trail.record(&&customer(), &read);
+        trail.record(&vec![customer(), customer()], &read);
+        trail.record(&[customer()], &read);
+
+        assert_eq!(trail.decisions[0].unauthorized_fields, vec!["email"]);
+        assert_eq!(
+            trail.decisions[1].unauthorized_fields,
+            vec!["0.email", "1.email"]
+        );
+        assert_eq!(trail.decisions[2].unauthorized_fields, vec!["0.email"]);
+    }
+}
@@ -0,0 +1,398 @@
+//! Cached authorization.
+//!
+//! [`CachedAuthorizor`](struct.CachedAuthorizor.html) keeps the results of previous
+//! authorizations in a LRU cache keyed by the authorized value and the canonical scope string.
+//! It is meant for hot paths where the same values are authorized over and over with the same
+//! scopes, e.g. a service account checked by a middleware on every request.
+//!
+//! Keys are compared in full, so authorized values must be `Clone + Hash + Eq`.
+//!
+//! A cache hit returns the stored result without authorizing again: field audit callbacks
+//! aren't called and nothing is logged. A result is never kept after the next
+//! [`field_expiries`](../trait.Authorizable.html#method.field_expiries) of its type, even without
+//! a TTL.
+
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use lru::LruCache;
+
+use crate::error::AuthorizedError;
+use crate::result::AuthorizedResult;
+use crate::scope::{IntoScope, Scope};
+use crate::{Authorizable, Authorizor};
+
+const DEFAULT_CAPACITY: usize = 128;
+
+struct CacheEntry<T> {
+    created_at: Instant,
+    /// The next field expiry when the result was computed, after which it is stale.
+    expires_at: Option<SystemTime>,
+    result: AuthorizedResult<T>,
+}
+
+/// The earliest field expiry of `A` which isn't past yet.
+fn next_field_expiry<A: Authorizable>() -> Option<SystemTime> {
+    let now = SystemTime::now();
+
+    A::field_expiries()
+        .iter()
+        .map(|(_, expiry)| UNIX_EPOCH + Duration::from_secs(*expiry))
+        .filter(|expiry| *expiry > now)
+        .min()
+}
+
+/// Hits and misses recorded by a [`CachedAuthorizor`](struct.CachedAuthorizor.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Ratio of authorizations served from the cache, between `0.0` and `1.0`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+struct CacheState<A, T> {
+    /// Keyed by the authorized value and the canonical string of the scope.
+    entries: LruCache<(A, String), CacheEntry<T>>,
+    stats: CacheStats,
+}
+
+/// Authorizor caching the results of [`Authorizable::authorize`](../trait.Authorizable.html).
+pub struct CachedAuthorizor<A: Authorizable> {
+    cache: Mutex<CacheState<A, A::Authorized>>,
+    ttl: Option<Duration>,
+    authorizable: PhantomData<fn(&A)>,
+}
+
+impl<A> CachedAuthorizor<A>
+where
+    A: Authorizable + Clone + Hash + Eq,
+    A::Authorized: Clone,
+{
+    /// Create a cached authorizor with the default capacity and no TTL.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    /// Create a [`CachedAuthorizorBuilder`](struct.CachedAuthorizorBuilder.html) to configure
+    /// the capacity and the TTL of the cache.
+    #[must_use]
+    pub fn builder() -> CachedAuthorizorBuilder<A> {
+        CachedAuthorizorBuilder {
+            capacity: NonZeroUsize::new(DEFAULT_CAPACITY).unwrap_or(NonZeroUsize::MIN),
+            ttl: None,
+            authorizable: PhantomData,
+        }
+    }
+
+    /// Authorize `inner` with `scope`, reusing a previous result for an equal value and the same
+    /// scope if it is still in the cache.
+    ///
+    /// A miss runs [`Authorizor::authorize_ref`](../struct.Authorizor.html#method.authorize_ref).
+    /// A hit skips the field audit callbacks and the `with_log` records.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the scope conversion or of the authorization. Errors are never
+    /// cached.
+    pub fn authorize_cached<T: IntoScope>(
+        &self,
+        inner: &A,
+        scope: &T,
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        let scope: Scope = scope.into_scope()?;
+        let key = (inner.clone(), scope.to_string());
+
+        {
+            let mut state = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+            let cached = state.entries.get(&key).and_then(|entry| {
+                if self.is_expired(entry) {
+                    None
+                } else {
+                    Some(entry.result.clone())
+                }
+            });
+
+            if let Some(result) = cached {
+                state.stats.hits += 1;
+                return Ok(result);
+            }
+
+            state.stats.misses += 1;
+        }
+
+        let expires_at = next_field_expiry::<A>();
+        let result = Authorizor::authorize_ref(inner, &scope)?;
+
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .put(
+                key,
+                CacheEntry {
+                    created_at: Instant::now(),
+                    expires_at,
+                    result: result.clone(),
+                },
+            );
+
+        Ok(result)
+    }
+
+    /// Hits and misses recorded since the creation of the authorizor.
+    pub fn stats(&self) -> CacheStats {
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .stats
+    }
+
+    /// Remove every cached result.
+    pub fn clear(&self) {
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .clear();
+    }
+
+    fn is_expired(&self, entry: &CacheEntry<A::Authorized>) -> bool {
+        self.ttl.is_some_and(|ttl| entry.created_at.elapsed() > ttl)
+            || entry
+                .expires_at
+                .is_some_and(|expires_at| SystemTime::now() >= expires_at)
+    }
+}
+
+impl<A> Default for CachedAuthorizor<A>
+where
+    A: Authorizable + Clone + Hash + Eq,
+    A::Authorized: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder of [`CachedAuthorizor`](struct.CachedAuthorizor.html).
+pub struct CachedAuthorizorBuilder<A: Authorizable> {
+    capacity: NonZeroUsize,
+    ttl: Option<Duration>,
+    authorizable: PhantomData<fn(&A)>,
+}
+
+impl<A> CachedAuthorizorBuilder<A>
+where
+    A: Authorizable + Clone + Hash + Eq,
+    A::Authorized: Clone,
+{
+    /// Maximum number of results kept in the cache.
+    #[must_use]
+    pub fn capacity(mut self, capacity: NonZeroUsize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Duration after which a cached result is computed again.
+    #[must_use]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> CachedAuthorizor<A> {
+        CachedAuthorizor {
+            cache: Mutex::new(CacheState {
+                entries: LruCache::new(self.capacity),
+                stats: CacheStats::default(),
+            }),
+            ttl: self.ttl,
+            authorizable: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Authorized)]
+    #[authorized(scope = "service")]
+    struct ServiceAccount {
+        name: String,
+        #[authorized(scope = "admin")]
+        token: String,
+    }
+
+    impl Authorized for ServiceAccount {}
+
+    fn account() -> ServiceAccount {
+        ServiceAccount {
+            name: "worker".into(),
+            token: "secret".into(),
+        }
+    }
+
+    #[test]
+    fn repeated_calls_hit_the_cache() -> Result<(), AuthorizedError> {
+        let account = account();
+        let authorizor = CachedAuthorizor::new();
+
+        for _ in 0..1000 {
+            let result = authorizor.authorize_cached(&account, &"service")?;
+            assert_eq!(result.status, AuthorizationStatus::Authorized);
+            assert_eq!(result.unauthorized_fields, vec!["token".to_string()]);
+        }
+
+        let stats = authorizor.stats();
+        assert_eq!(
+            stats,
+            CacheStats {
+                hits: 999,
+                misses: 1
+            }
+        );
+        assert!(stats.hit_rate() > 0.99);
+
+        // Same tokens in a different order share the cached result.
+        authorizor.authorize_cached(&account, &"admin service")?;
+        authorizor.authorize_cached(&account, &"service admin")?;
+        assert_eq!(authorizor.stats().misses, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn results_are_cached_per_value() -> Result<(), AuthorizedError> {
+        let worker = account();
+        let scheduler = ServiceAccount {
+            name: "scheduler".into(),
+            token: "other secret".into(),
+        };
+        let authorizor = CachedAuthorizor::new();
+
+        let result = authorizor.authorize_cached(&worker, &"service admin")?;
+        assert_eq!(result.inner.name, "worker");
+
+        let result = authorizor.authorize_cached(&scheduler, &"service admin")?;
+        assert_eq!(result.inner.name, "scheduler");
+        assert_eq!(result.inner.token, "other secret");
+        assert_eq!(authorizor.stats(), CacheStats { hits: 0, misses: 2 });
+
+        authorizor.authorize_cached(&worker, &"service admin")?;
+        assert_eq!(authorizor.stats(), CacheStats { hits: 1, misses: 2 });
+
+        Ok(())
+    }
+
+    #[test]
+    fn expired_results_are_computed_again() -> Result<(), AuthorizedError> {
+        let account = account();
+        let authorizor = CachedAuthorizor::builder()
+            .capacity(NonZeroUsize::new(1).unwrap())
+            .ttl(Duration::from_secs(0))
+            .build();
+
+        authorizor.authorize_cached(&account, &"service")?;
+        std::thread::sleep(Duration::from_millis(1));
+        authorizor.authorize_cached(&account, &"service")?;
+
+        assert_eq!(authorizor.stats(), CacheStats { hits: 0, misses: 2 });
+
+        Ok(())
+    }
+
+    /// Its `price` expires one second after it is created.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Offer {
+        price: u32,
+    }
+
+    static OFFER_EXPIRIES: OnceLock<[(&str, u64); 1]> = OnceLock::new();
+
+    impl Authorized for Offer {}
+
+    impl Authorizable for Offer {
+        type Authorized = u32;
+
+        fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+            input: &Self,
+            unauthorized_fields: &[S],
+        ) -> Result<Self::Authorized, AuthorizedError> {
+            Ok(if unauthorized_fields.is_empty() {
+                input.price
+            } else {
+                0
+            })
+        }
+
+        fn filter_unauthorized_fields(_input: &Self, _scope: &Scope) -> UnAuthorizedFields {
+            Self::field_expiries()
+                .iter()
+                .filter(|(_, expiry)| crate::is_expired(*expiry))
+                .map(|(field, _)| (*field).to_string())
+                .collect()
+        }
+
+        fn field_expiries() -> &'static [(&'static str, u64)] {
+            OFFER_EXPIRIES.get_or_init(|| {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+                [("price", now.as_secs() + 1)]
+            })
+        }
+
+        fn authorize(
+            input: &Self,
+            authorizer: &Scope,
+        ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+            let unauthorized_fields = Self::filter_unauthorized_fields(input, authorizer);
+
+            Ok(AuthorizedResult {
+                inner: Self::builder_authorized_struct(input, &unauthorized_fields)?,
+                input_scope: authorizer.clone(),
+                status: AuthorizationStatus::Authorized,
+                unauthorized_fields,
+            })
+        }
+    }
+
+    #[test]
+    fn results_are_not_kept_after_a_field_expiry() -> Result<(), AuthorizedError> {
+        let offer = Offer { price: 5 };
+        let authorizor = CachedAuthorizor::new();
+
+        assert_eq!(authorizor.authorize_cached(&offer, &"user")?.inner, 5);
+        assert_eq!(authorizor.authorize_cached(&offer, &"user")?.inner, 5);
+        assert_eq!(authorizor.stats(), CacheStats { hits: 1, misses: 1 });
+
+        let expiry = UNIX_EPOCH + Duration::from_secs(Offer::field_expiries()[0].1);
+        std::thread::sleep(expiry.duration_since(SystemTime::now()).unwrap_or_default());
+
+        assert_eq!(authorizor.authorize_cached(&offer, &"user")?.inner, 0);
+        assert_eq!(authorizor.stats(), CacheStats { hits: 1, misses: 2 });
+
+        Ok(())
+    }
+}
@@ -1,9 +1,102 @@
-use crate::scope::ParseScopeErr;
+use std::error::Error;
+use std::fmt;
+use std::io;
 
+use crate::scope::{ParseScopeErr, Scope};
+
+/// The scopes carried by the variants are boxed: a [`Scope`](../scope/struct.Scope.html) holds
+/// two sets, boxing them keeps `AuthorizedError`, and every `Result` returning it, small
+/// (see `clippy::result_large_err`).
 #[derive(Debug)]
 pub enum AuthorizedError {
     MultipleAuthorizedErrors(Vec<AuthorizedError>),
     ParseScopeError(ParseScopeErr),
+    /// The provided scope doesn't give access to the whole structure, returned by
+    /// [`Authorizor::authorize_strict`](../struct.Authorizor.html#method.authorize_strict), or
+    /// by a function annotated with `#[authorize_route]` without any field.
+    InsufficientScope {
+        /// The fields that have been redacted.
+        required_fields: Vec<String>,
+        provided_scope: Box<Scope>,
+    },
+    /// The scope grants a token which isn't part of a
+    /// [`ScopeWhitelist`](../whitelist/struct.ScopeWhitelist.html).
+    DisallowedScope(String),
+    /// Some fields of the structure are unauthorized, returned by
+    /// [`Authorizor::authorize_all_fields`](../struct.Authorizor.html#method.authorize_all_fields).
+    PartiallyUnauthorized {
+        struct_type: &'static str,
+        unauthorized_fields: Vec<String>,
+        /// The scope required to access the unauthorized fields.
+        scope: Box<Scope>,
+    },
+    /// The structure is unauthorized, returned by
+    /// [`Authorizor::authorize_or_unauthorized`](../struct.Authorizor.html#method.authorize_or_unauthorized).
+    UnAuthorized(UnauthorizedResult),
+    /// An item of a list is unauthorized, returned by
+    /// [`Authorizor::authorize_many_strict`](../struct.Authorizor.html#method.authorize_many_strict).
+    ItemUnauthorized {
+        /// The position of the item in the list.
+        index: usize,
+        inner: Box<AuthorizedError>,
+    },
+}
+
+/// The scope of an authorization doesn't match the scope of the structure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnauthorizedResult {
+    pub input_scope: Scope,
+    pub struct_type: &'static str,
+}
+
+impl fmt::Display for UnauthorizedResult {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "Scope `{}` is not authorized to access {}",
+            self.input_scope, self.struct_type
+        )
+    }
+}
+
+impl Error for UnauthorizedResult {}
+
+impl From<UnauthorizedResult> for AuthorizedError {
+    fn from(error: UnauthorizedResult) -> Self {
+        Self::UnAuthorized(error)
+    }
+}
+
+impl fmt::Display for AuthorizedError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::MultipleAuthorizedErrors(errors) => {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(fmt, "Multiple authorization errors: {}", errors.join(", "))
+            }
+            Self::ParseScopeError(error) => error.fmt(fmt),
+            Self::InsufficientScope {
+                required_fields,
+                provided_scope,
+            } => write!(
+                fmt,
+                "Scope `{provided_scope}` is insufficient, unauthorized fields: {}",
+                required_fields.join(", ")
+            ),
+            Self::DisallowedScope(token) => write!(fmt, "Scope token is not allowed: {token}"),
+            Self::PartiallyUnauthorized {
+                struct_type,
+                unauthorized_fields,
+                scope,
+            } => write!(
+                fmt,
+                "Fields {} of {struct_type} are unauthorized, a scope matching `{scope}` is required to access them",
+                unauthorized_fields.join(", ")
+            ),
+            Self::UnAuthorized(error) => error.fmt(fmt),
+            Self::ItemUnauthorized { index, inner } => write!(fmt, "Item {index}: {inner}"),
+        }
+    }
 }
 
 impl From<ParseScopeErr> for AuthorizedError {
@@ -11,3 +104,54 @@ impl From<ParseScopeErr> for AuthorizedError {
         Self::ParseScopeError(error)
     }
 }
+
+impl Error for AuthorizedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ParseScopeError(error) => Some(error),
+            Self::UnAuthorized(error) => Some(error),
+            Self::ItemUnauthorized { inner, .. } => Some(inner.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Wraps the error message in an `InvalidData` error. The error type is lost.
+impl From<ParseScopeErr> for io::Error {
+    fn from(error: ParseScopeErr) -> Self {
+        Self::new(io::ErrorKind::InvalidData, error.to_string())
+    }
+}
+
+/// Wraps the error message in an `InvalidData` error. The error type is lost.
+impl From<AuthorizedError> for io::Error {
+    fn from(error: AuthorizedError) -> Self {
+        Self::new(io::ErrorKind::InvalidData, error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_scope(scope: &str) -> io::Result<Scope> {
+        Ok(scope.parse::<Scope>()?)
+    }
+
+    #[test]
+    fn errors_convert_to_io_and_boxed_errors() {
+        let error = read_scope("read:\"user\"").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "Encountered invalid character in scope: \""
+        );
+
+        let error: io::Error = AuthorizedError::DisallowedScope("admin".into()).into();
+        assert_eq!(error.to_string(), "Scope token is not allowed: admin");
+
+        let boxed: Box<dyn Error + Send + Sync> =
+            AuthorizedError::from(ParseScopeErr::InvalidCharacter('"')).into();
+        assert!(boxed.source().is_some());
+    }
+}
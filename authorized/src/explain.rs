@@ -0,0 +1,178 @@
+//! Human readable explanations of authorization decisions.
+//!
+//! [`Authorizor::explain`](../struct.Authorizor.html#method.explain) builds an
+//! [`AuthorizationExplanation`](struct.AuthorizationExplanation.html) detailing the global
+//! scope check and every field decision. Its `Display` output is meant for developers, e.g. as
+//! an assertion message:
+//!
+//! ```
+//! use authorized::prelude::*;
+//!
+//! #[derive(Authorized)]
+//! #[authorized(scope = "read:user")]
+//! struct User {
+//!     #[authorized(scope = "admin")]
+//!     email: String,
+//! }
+//!
+//! impl Authorized for User {}
+//!
+//! let user = User { email: "email".into() };
+//! let explanation = Authorizor::explain(&user, &"read:user").unwrap();
+//!
+//! assert!(explanation.global_scope_passed, "{}", explanation);
+//! ```
+//!
+//! The reasons aren't stable, they shouldn't be parsed.
+
+use std::fmt;
+
+use crate::scope::Scope;
+use crate::Authorizable;
+
+/// The decision taken for a single field, see
+/// [`AuthorizationExplanation`](struct.AuthorizationExplanation.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldDecision {
+    pub field_name: &'static str,
+    /// The scope of the field, empty for unrestricted fields.
+    pub required_scope: Scope,
+    pub provided_scope: Scope,
+    pub authorized: bool,
+    pub reason: String,
+}
+
+/// The global and per-field decisions of an authorization.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthorizationExplanation {
+    pub struct_type: &'static str,
+    pub global_scope_passed: bool,
+    /// The global scope of the structure, empty when it has none.
+    pub global_scope_required: Scope,
+    pub field_decisions: Vec<FieldDecision>,
+}
+
+impl AuthorizationExplanation {
+    pub(crate) fn new<A: Authorizable>(inner: &A, scope: &Scope) -> Self {
+        let requirements = A::scope_requirements();
+        let global_scope_passed = match &requirements.global_scope {
+            Some(global) => global.allow_access(scope),
+            None => true,
+        };
+
+        let field_decisions = A::field_names()
+            .iter()
+            .map(|&field_name| {
+                let authorized = A::is_authorized_for_field(inner, field_name, scope);
+
+                match requirements.field_scopes.get(field_name) {
+                    Some(required) => FieldDecision {
+                        field_name,
+                        required_scope: required.clone(),
+                        provided_scope: scope.clone(),
+                        authorized,
+                        reason: required.debug_explain(scope),
+                    },
+                    None => FieldDecision {
+                        field_name,
+                        required_scope: Scope::default(),
+                        provided_scope: scope.clone(),
+                        authorized,
+                        reason: "No scope required.".to_string(),
+                    },
+                }
+            })
+            .collect();
+
+        Self {
+            struct_type: std::any::type_name::<A>(),
+            global_scope_passed,
+            global_scope_required: requirements.global_scope.unwrap_or_default(),
+            field_decisions,
+        }
+    }
+}
+
+/// A line for the global scope then a line per field.
+impl fmt::Display for AuthorizationExplanation {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let decision = |authorized| if authorized { "granted" } else { "denied" };
+
+        write!(
+            fmt,
+            "{}: global scope `{}` {}",
+            self.struct_type,
+            self.global_scope_required,
+            decision(self.global_scope_passed)
+        )?;
+
+        for field in &self.field_decisions {
+            write!(
+                fmt,
+                "\n  - {}: {}, {}",
+                field.field_name,
+                decision(field.authorized),
+                field.reason
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::scope::ParseScopeErr;
+
+    #[derive(Debug, Authorized)]
+    #[authorized(scope = "read:user")]
+    struct Customer {
+        id: i32,
+        #[authorized(scope = "admin")]
+        email: String,
+    }
+
+    impl Authorized for Customer {}
+
+    #[test]
+    fn every_decision_is_explained() -> Result<(), ParseScopeErr> {
+        let customer = Customer {
+            id: 1,
+            email: "email".into(),
+        };
+
+        let explanation = Authorizor::explain(&customer, &"read:user")?;
+        assert!(explanation.global_scope_passed, "{}", explanation);
+        assert_eq!(
+            explanation.global_scope_required,
+            "read:user".parse().unwrap()
+        );
+        assert_eq!(explanation.field_decisions.len(), 2);
+
+        let id = &explanation.field_decisions[0];
+        assert!(id.authorized);
+        assert_eq!(id.required_scope, "".parse().unwrap());
+
+        let email = &explanation.field_decisions[1];
+        assert_eq!(email.field_name, "email");
+        assert!(!email.authorized);
+        assert_eq!(email.required_scope, "admin".parse().unwrap());
+        assert_eq!(email.provided_scope, "read:user".parse().unwrap());
+        assert!(email.reason.contains("Missing tokens: [admin]"));
+
+        let report = explanation.to_string();
+        assert!(report.ends_with("global scope `read:user` granted\n  - id: granted, No scope required.\n  - email: denied, Access denied: resource requires [admin] but authorizer only has [read:user]. Denied by: []. Missing tokens: [admin]."), "{}", report);
+
+        let explanation = Authorizor::explain(&customer, &"admin")?;
+        assert!(!explanation.global_scope_passed);
+        assert!(explanation
+            .field_decisions
+            .iter()
+            .all(|field| field.authorized));
+
+        assert!(Authorizor::explain(&customer, &"\"admin\"").is_err());
+
+        Ok(())
+    }
+}
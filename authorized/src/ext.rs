@@ -0,0 +1,132 @@
+//! Authorization of iterators.
+//!
+//! [`AuthorizedExt::authorize_all`](trait.AuthorizedExt.html#method.authorize_all) authorizes
+//! the items of any iterator lazily, without collecting them in a `Vec<T>` first.
+
+use crate::error::AuthorizedError;
+use crate::result::AuthorizedResult;
+use crate::scope::Scope;
+use crate::Authorizable;
+
+/// Extension trait adding authorization adapters to every iterator.
+pub trait AuthorizedExt: Iterator + Sized {
+    /// Authorize every item with `scope` as it is polled.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// #[derive(Clone, Authorized)]
+    /// struct User {
+    ///     id: i32,
+    ///     #[authorized(scope = "admin")]
+    ///     email: String,
+    /// }
+    ///
+    /// impl Authorized for User {}
+    ///
+    /// let users = vec![User { id: 1, email: "email".into() }];
+    /// let scope = "read:user".parse::<Scope>().unwrap();
+    ///
+    /// for user in users.into_iter().authorize_all(&scope) {
+    ///     assert!(user.unwrap().inner.email.is_empty());
+    /// }
+    /// ```
+    fn authorize_all(self, scope: &Scope) -> AuthorizedIterator<'_, Self>
+    where
+        Self::Item: Authorizable,
+    {
+        AuthorizedIterator { iter: self, scope }
+    }
+}
+
+impl<I: Iterator> AuthorizedExt for I {}
+
+/// Iterator created by [`AuthorizedExt::authorize_all`](trait.AuthorizedExt.html#method.authorize_all).
+#[derive(Clone, Debug)]
+pub struct AuthorizedIterator<'a, I> {
+    iter: I,
+    scope: &'a Scope,
+}
+
+impl<I> Iterator for AuthorizedIterator<'_, I>
+where
+    I: Iterator,
+    I::Item: Authorizable,
+{
+    type Item = Result<AuthorizedResult<<I::Item as Authorizable>::Authorized>, AuthorizedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|item| Authorizable::authorize(&item, self.scope))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Authorize every item of `iter` with `scope`, keeping the errors.
+pub fn collect_authorized<T: Authorizable>(
+    iter: impl Iterator<Item = T>,
+    scope: &Scope,
+) -> Vec<Result<AuthorizedResult<T::Authorized>, AuthorizedError>> {
+    iter.authorize_all(scope).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Clone, PartialEq, Authorized)]
+    struct Order {
+        id: i32,
+        #[authorized(scope = "billing")]
+        amount: u64,
+    }
+
+    impl Authorized for Order {}
+
+    fn orders() -> Vec<Order> {
+        (1..=3).map(|id| Order { id, amount: 100 }).collect()
+    }
+
+    #[test]
+    fn iterators_are_authorized_like_vecs() -> Result<(), AuthorizedError> {
+        let scope = "user".parse::<Scope>()?;
+
+        let expected = Authorizor::authorize(&orders(), &scope)?.inner;
+        let lazy = orders()
+            .into_iter()
+            .authorize_all(&scope)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(lazy, expected);
+        assert_eq!(lazy[2].inner, Order { id: 3, amount: 0 });
+
+        let collected = collect_authorized(orders().into_iter(), &scope);
+        assert_eq!(collected.len(), 3);
+        assert!(collected.iter().all(Result::is_ok));
+
+        Ok(())
+    }
+
+    #[test]
+    fn items_are_authorized_lazily() -> Result<(), AuthorizedError> {
+        let scope = "billing".parse::<Scope>()?;
+        let mut polled = 0;
+
+        let first = orders()
+            .into_iter()
+            .inspect(|_| polled += 1)
+            .authorize_all(&scope)
+            .next()
+            .unwrap()?;
+
+        assert_eq!(first.inner.amount, 100);
+        assert_eq!(polled, 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+//! Recovery from failing field defaults.
+//!
+//! The value of an unauthorized field is built by its `default` function. When one of these
+//! functions panics the whole authorization panics with it. While
+//! [`Authorizor::authorize_fallible_fields`](../struct.Authorizor.html#method.authorize_fallible_fields)
+//! or [`Authorizable::authorize_recover`](../trait.Authorizable.html#method.authorize_recover)
+//! runs, the panic is caught instead: the field falls back to `Default::default()` and a
+//! [`FieldBuildError`](struct.FieldBuildError.html) is recorded. Fields whose type doesn't
+//! implement `Default` have nothing to fall back to, so their panic is not caught.
+//!
+//! Like the [audit callback](../audit/index.html), the recorded errors are kept in a thread
+//! local so they don't need to be threaded through the
+//! [`Authorizable`](../trait.Authorizable.html) methods.
+//!
+//! The panic hook still runs for caught panics, so their message is printed as usual.
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+
+thread_local! {
+    static FIELD_BUILD_ERRORS: RefCell<Option<Vec<FieldBuildError>>> = const { RefCell::new(None) };
+}
+
+/// A field whose `default` function panicked while building the authorized value.
+#[derive(Debug)]
+pub struct FieldBuildError {
+    pub field_name: String,
+    /// The panic payload.
+    pub error: Box<dyn Any + Send>,
+}
+
+/// Catches field build panics on the current thread until dropped, then restores the previous
+/// state.
+pub(crate) struct RecoverGuard {
+    previous: Option<Vec<FieldBuildError>>,
+}
+
+impl RecoverGuard {
+    pub(crate) fn register() -> Self {
+        let previous = FIELD_BUILD_ERRORS.with(|cell| cell.replace(Some(vec![])));
+
+        Self { previous }
+    }
+
+    /// Unregister the guard and return the errors recorded since its registration.
+    pub(crate) fn into_errors(self) -> Vec<FieldBuildError> {
+        let errors = FIELD_BUILD_ERRORS.with(|cell| {
+            cell.borrow_mut()
+                .as_mut()
+                .map(std::mem::take)
+                .unwrap_or_default()
+        });
+        drop(self);
+
+        errors
+    }
+}
+
+impl Drop for RecoverGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        FIELD_BUILD_ERRORS.with(|cell| cell.replace(previous));
+    }
+}
+
+/// Checks if field build panics are caught on the current thread.
+///
+/// This is called by the code generated by `#[derive(Authorized)]`, which only goes through
+/// [`FieldBuilder`](struct.FieldBuilder.html) while recovering.
+#[doc(hidden)]
+#[must_use]
+pub fn is_recovering() -> bool {
+    FIELD_BUILD_ERRORS.with(|cell| cell.borrow().is_some())
+}
+
+/// Builds the value of an unauthorized field with its `default` function while recovering.
+///
+/// When the field type implements `Default`, [`RecoverWithDefault`](trait.RecoverWithDefault.html)
+/// catches the panic and falls back to `Default::default()`. Otherwise
+/// [`PropagatePanic`](trait.PropagatePanic.html) is picked and the panic goes through, as there
+/// is no value to fall back to.
+///
+/// This is called by the code generated by `#[derive(Authorized)]`.
+#[doc(hidden)]
+pub struct FieldBuilder<F> {
+    field_name: &'static str,
+    build: Cell<Option<F>>,
+}
+
+impl<F> FieldBuilder<F> {
+    pub fn new(field_name: &'static str, build: F) -> Self {
+        Self {
+            field_name,
+            build: Cell::new(Some(build)),
+        }
+    }
+
+    fn take(&self) -> F {
+        self.build.take().expect("a field is only built once")
+    }
+}
+
+#[doc(hidden)]
+pub trait RecoverWithDefault {
+    type Output;
+
+    fn build(&self) -> Self::Output;
+}
+
+impl<T: Default, F: FnOnce() -> T> RecoverWithDefault for FieldBuilder<F> {
+    type Output = T;
+
+    fn build(&self) -> T {
+        match panic::catch_unwind(AssertUnwindSafe(self.take())) {
+            Ok(value) => value,
+            Err(error) => {
+                FIELD_BUILD_ERRORS.with(|cell| {
+                    if let Some(errors) = cell.borrow_mut().as_mut() {
+                        errors.push(FieldBuildError {
+                            field_name: self.field_name.to_string(),
+                            error,
+                        });
+                    }
+                });
+
+                T::default()
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait PropagatePanic {
+    type Output;
+
+    fn build(&self) -> Self::Output;
+}
+
+impl<T, F: FnOnce() -> T> PropagatePanic for &FieldBuilder<F> {
+    type Output = T;
+
+    fn build(&self) -> T {
+        (self.take())()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn lookup_nickname() -> String {
+        panic!("nickname not found")
+    }
+
+    #[derive(Debug, Clone, Authorized)]
+    struct Member {
+        id: i32,
+        #[authorized(scope = "admin", default = "lookup_nickname")]
+        nickname: String,
+    }
+
+    impl Authorized for Member {}
+
+    #[derive(Debug, Clone, Authorized)]
+    struct Visitor {
+        #[authorized(scope = "admin", or_default)]
+        visits: u32,
+    }
+
+    impl Authorized for Visitor {}
+
+    /// A field type without `Default`.
+    #[derive(Debug, Clone, PartialEq)]
+    struct Token(&'static str);
+
+    fn hidden_token() -> Token {
+        Token("hidden")
+    }
+
+    fn missing_token() -> Token {
+        panic!("token not found")
+    }
+
+    #[derive(Debug, Clone, Authorized)]
+    struct Session {
+        #[authorized(scope = "admin", default = "hidden_token")]
+        token: Token,
+        #[authorized(scope = "admin", default = "Vec::new")]
+        history: Vec<u32>,
+    }
+
+    impl Authorized for Session {}
+
+    #[derive(Debug, Clone, Authorized)]
+    struct BrokenSession {
+        #[authorized(scope = "admin", default = "missing_token")]
+        token: Token,
+    }
+
+    impl Authorized for BrokenSession {}
+
+    #[test]
+    fn or_default_fields_use_the_default_of_their_type() -> Result<(), AuthorizedError> {
+        let visitor = Visitor { visits: 42 };
+
+        let (result, errors) = Authorizor::authorize_fallible_fields(&visitor, &"user")?;
+        assert_eq!(result.inner.visits, 0);
+        assert!(errors.is_empty());
+
+        assert_eq!(Authorizor::authorize(&visitor, &"admin")?.inner.visits, 42);
+
+        Ok(())
+    }
+
+    #[test]
+    fn panicking_defaults_fall_back_to_default() -> Result<(), AuthorizedError> {
+        let member = Member {
+            id: 1,
+            nickname: "nick".into(),
+        };
+
+        let (result, errors) = Authorizor::authorize_fallible_fields(&member, &"user")?;
+        assert_eq!(result.status, AuthorizationStatus::Authorized);
+        assert_eq!(result.unauthorized_fields, vec!["nickname".to_string()]);
+        assert_eq!(result.inner.id, 1);
+        assert!(result.inner.nickname.is_empty());
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field_name, "nickname");
+        assert_eq!(
+            errors[0].error.downcast_ref::<&str>(),
+            Some(&"nickname not found")
+        );
+
+        let (inner, errors) = Member::authorize_recover(&member, &["nickname"])?;
+        assert!(inner.nickname.is_empty());
+        assert_eq!(errors.len(), 1);
+
+        let (inner, errors) = Member::authorize_recover(&member, &[] as &[&str])?;
+        assert_eq!(inner.nickname, "nick");
+        assert!(errors.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn defaults_do_not_require_default_outside_of_recovery() -> Result<(), AuthorizedError> {
+        let session = Session {
+            token: Token("secret"),
+            history: vec![1, 2],
+        };
+
+        let result = Authorizor::authorize(&session, &"user")?;
+        assert_eq!(result.inner.token, Token("hidden"));
+        assert!(result.inner.history.is_empty());
+
+        let (result, errors) = Authorizor::authorize_fallible_fields(&session, &"user")?;
+        assert_eq!(result.inner.token, Token("hidden"));
+        assert!(errors.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn panics_without_default_are_not_caught() {
+        let session = BrokenSession {
+            token: Token("secret"),
+        };
+
+        let result = std::panic::catch_unwind(|| {
+            Authorizor::authorize_fallible_fields(&session, &"user").map(|_| ())
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn panics_are_not_caught_outside_of_recovery() {
+        let member = Member {
+            id: 1,
+            nickname: "nick".into(),
+        };
+
+        let result =
+            std::panic::catch_unwind(|| Authorizor::authorize(&member, &"user").map(|_| ()));
+        assert!(result.is_err());
+    }
+}
@@ -13,7 +13,23 @@
 #![allow(clippy::module_name_repetitions)]
 #![recursion_limit = "256"]
 
+// Lets the derive macro be used in the crate's own tests.
+#[cfg(test)]
+extern crate self as authorized;
+
+pub mod audit;
+pub mod explain;
+pub mod ext;
+pub mod fallible;
 pub mod scope;
+pub mod whitelist;
+
+#[cfg(feature = "with_actix")]
+pub mod actix;
+#[cfg(feature = "with_cache")]
+pub mod cache;
+#[cfg(feature = "with_validator")]
+pub mod validation;
 
 mod error;
 mod result;
@@ -22,14 +38,145 @@ mod serde;
 
 pub mod prelude;
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::rc::Rc;
+
+use audit::{AuditedAuthorizor, FieldAuditEvent};
+use explain::AuthorizationExplanation;
+use fallible::{FieldBuildError, RecoverGuard};
+
 use scope::IntoScope;
+use scope::ParseScopeErr;
 use scope::Scope;
+use whitelist::ScopeWhitelist;
+
+#[cfg(feature = "with_serde")]
+pub use crate::serde::{
+    is_field_unauthorized, scope_as_string, MaybeAuthorized, SerializationMode,
+    SerializingAuthorizor,
+};
 
-use error::*;
-use result::*;
+use error::{AuthorizedError, UnauthorizedResult};
+use result::{AuthorizationStatus, AuthorizedResult, LazyAuthorizedResult};
 
 pub type UnAuthorizedFields = Vec<String>;
 
+/// The name reported in place of unauthorized `#[authorized(sensitive)]` fields, so their real
+/// name doesn't leak through [`UnAuthorizedFields`](type.UnAuthorizedFields.html) or errors.
+///
+/// Every sensitive field of a struct shares this name in the lists returned to the caller. The
+/// authorization itself still uses the real names: each sensitive field is only redacted when
+/// its own scope is missing.
+pub const SENSITIVE_FIELD: &str = "<sensitive_field>";
+
+/// Replaces the names of the sensitive fields of `A` by
+/// [`SENSITIVE_FIELD`](constant.SENSITIVE_FIELD.html), listed once.
+///
+/// This is called by the code generated by `#[derive(Authorized)]`.
+#[doc(hidden)]
+#[must_use]
+pub fn mask_sensitive_fields<A: Authorizable + ?Sized>(
+    fields: UnAuthorizedFields,
+) -> UnAuthorizedFields {
+    let mut masked = UnAuthorizedFields::with_capacity(fields.len());
+
+    for field in fields {
+        if !A::is_sensitive_field(&field) {
+            masked.push(field);
+        } else if !masked.iter().any(|f| f == SENSITIVE_FIELD) {
+            masked.push(SENSITIVE_FIELD.to_string());
+        }
+    }
+
+    masked
+}
+
+/// Authorizes `input` with each scope of a chain, see
+/// [`Authorizor::authorize_chain`](struct.Authorizor.html#method.authorize_chain). `step` returns
+/// the status and the real names of the unauthorized fields for one scope, the structure is
+/// built once at the end.
+fn authorize_steps<A, F>(
+    input: &A,
+    scopes: &[Scope],
+    mut step: F,
+) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError>
+where
+    A: Authorizable + ?Sized,
+    F: FnMut(&Scope) -> Result<(AuthorizationStatus, UnAuthorizedFields), AuthorizedError>,
+{
+    let input_scope = scopes.last().cloned().unwrap_or_default();
+    let steps = if scopes.is_empty() {
+        std::slice::from_ref(&input_scope)
+    } else {
+        scopes
+    };
+
+    let mut unauthorized_fields = UnAuthorizedFields::new();
+    let mut status = AuthorizationStatus::Authorized;
+
+    for scope in steps {
+        let (step_status, fields) = step(scope)?;
+        if step_status == AuthorizationStatus::UnAuthorized {
+            status = AuthorizationStatus::UnAuthorized;
+        }
+
+        for field in fields {
+            if !unauthorized_fields.contains(&field) {
+                unauthorized_fields.push(field);
+            }
+        }
+    }
+
+    Ok(AuthorizedResult {
+        inner: A::builder_authorized_struct(input, &unauthorized_fields)?,
+        input_scope,
+        status,
+        unauthorized_fields: mask_sensitive_fields::<A>(unauthorized_fields),
+    })
+}
+
+/// Authorizes `input` with each scope of a chain without building it at each step: a step only
+/// filters the fields and checks `global_scopes`, like
+/// [`Authorizable::authorize`](trait.Authorizable.html#tymethod.authorize) does.
+///
+/// This is called by the code generated by `#[derive(Authorized)]`.
+///
+/// # Errors
+///
+/// Returns the error of the build of the structure.
+#[doc(hidden)]
+pub fn authorize_chain_with_global_scopes<A: Authorizable + ?Sized>(
+    input: &A,
+    scopes: &[Scope],
+    global_scopes: &[Scope],
+) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+    authorize_steps(input, scopes, |scope| {
+        let status = if global_scopes.is_empty()
+            || global_scopes
+                .iter()
+                .any(|global| global.allow_access(scope))
+        {
+            AuthorizationStatus::Authorized
+        } else {
+            AuthorizationStatus::UnAuthorized
+        };
+
+        Ok((status, A::filter_unauthorized_fields(input, scope)))
+    })
+}
+
+/// The scopes required by an [`Authorizable`](trait.Authorizable.html) type, see
+/// [`Authorizor::build_scope_for`](struct.Authorizor.html#method.build_scope_for).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScopeRequirements {
+    /// The scope required to authorize the structure.
+    pub global_scope: Option<Scope>,
+    /// The scope required by each scoped field, by field name.
+    pub field_scopes: HashMap<String, Scope>,
+}
+
 pub trait Authorizable {
     type Authorized;
 
@@ -42,6 +189,217 @@ pub trait Authorizable {
     ) -> Result<Self::Authorized, AuthorizedError>;
     fn filter_unauthorized_fields(input: &Self, scope: &Scope) -> UnAuthorizedFields;
 
+    /// Names of the fields of the structure, in declaration order.
+    ///
+    /// `#[derive(Authorized)]` lists every named field. The default implementation returns an
+    /// empty list.
+    #[must_use]
+    fn field_names() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Names of the fields of the structure paired with the scope required to access them, or
+    /// `None` for unrestricted fields, in declaration order.
+    ///
+    /// `#[derive(Authorized)]` lists every named field. The default implementation returns an
+    /// empty list.
+    #[must_use]
+    fn scoped_field_names() -> &'static [(&'static str, Option<&'static str>)] {
+        &[]
+    }
+
+    /// The scope required to access `field_name`, if any.
+    ///
+    /// `#[derive(Authorized)]` returns the `scope` attribute of the field. The default
+    /// implementation returns `None`.
+    #[must_use]
+    fn field_scope(_field_name: &str) -> Option<&'static str> {
+        None
+    }
+
+    /// Checks if `field_name` is reported as [`SENSITIVE_FIELD`](constant.SENSITIVE_FIELD.html)
+    /// in the lists returned to the caller.
+    ///
+    /// `#[derive(Authorized)]` returns `true` for the fields marked `sensitive`. The default
+    /// implementation returns `false`.
+    #[must_use]
+    fn is_sensitive_field(_field_name: &str) -> bool {
+        false
+    }
+
+    /// Names of the fields which are unauthorized from a given time, whatever the scope, paired
+    /// with that time as a Unix timestamp.
+    ///
+    /// `#[derive(Authorized)]` lists the fields with an `expiry`. The default implementation
+    /// returns an empty list.
+    #[must_use]
+    fn field_expiries() -> &'static [(&'static str, u64)] {
+        &[]
+    }
+
+    /// The scopes required by this type.
+    ///
+    /// `#[derive(Authorized)]` returns the `scope` attributes of the struct and of its fields.
+    /// The default implementation returns empty requirements.
+    #[must_use]
+    fn scope_requirements() -> ScopeRequirements {
+        ScopeRequirements::default()
+    }
+
+    /// Checks if `scope` can access the field named `field` of `input`, without building the
+    /// authorized value. Unknown fields are authorized.
+    ///
+    /// The default implementation runs
+    /// [`filter_unauthorized_fields`](#tymethod.filter_unauthorized_fields);
+    /// `#[derive(Authorized)]` only checks the scope of `field`.
+    #[must_use]
+    fn is_authorized_for_field(input: &Self, field: &str, scope: &Scope) -> bool {
+        !Self::filter_unauthorized_fields(input, scope)
+            .iter()
+            .any(|f| f == field)
+    }
+
+    /// Consume `self` and authorize it with `scope`, same as
+    /// [`Authorizor::authorize`](struct.Authorizor.html#method.authorize).
+    ///
+    /// # Errors
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// #[derive(Clone, Authorized)]
+    /// struct User {
+    ///     id: i32,
+    ///     #[authorized(scope = "admin")]
+    ///     email: String,
+    /// }
+    ///
+    /// impl Authorized for User {}
+    ///
+    /// let user = User { id: 1, email: "email".into() };
+    ///
+    /// let res = user.authorized_by("read:user").unwrap();
+    /// assert!(res.inner.email.is_empty());
+    ///
+    /// let res = user.into_authorized("admin read:user").unwrap();
+    /// assert_eq!(res.inner.email, "email");
+    /// ```
+    fn into_authorized<T: IntoScope>(
+        self,
+        scope: T,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError>
+    where
+        Self: Sized,
+    {
+        Authorizor::authorize(&self, &scope)
+    }
+
+    /// Authorize `self` with `scope`, same as
+    /// [`Authorizor::authorize`](struct.Authorizor.html#method.authorize).
+    ///
+    /// # Errors
+    ///
+    fn authorized_by<T: IntoScope>(
+        &self,
+        scope: T,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError>
+    where
+        Self: Sized,
+    {
+        Authorizor::authorize(self, &scope)
+    }
+
+    /// Filters the fields using a different scope for each field: a field is checked against
+    /// the scope of its name in `field_scopes`. Fields that aren't listed are authorized.
+    ///
+    /// The default implementation calls
+    /// [`is_authorized_for_field`](#method.is_authorized_for_field) once per listed field. The
+    /// fields are returned in declaration order, see [`field_names`](#method.field_names), then
+    /// by name.
+    fn filter_unauthorized_fields_with_map(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> UnAuthorizedFields {
+        let mut field_scopes: Vec<(&str, &Scope)> = field_scopes
+            .iter()
+            .map(|(field, scope)| (*field, scope))
+            .collect();
+        field_scopes.sort_by_key(|(field, _)| {
+            let position = Self::field_names().iter().position(|name| name == field);
+
+            (position.is_none(), position, *field)
+        });
+
+        field_scopes
+            .into_iter()
+            .filter(|(field, scope)| !Self::is_authorized_for_field(input, field, scope))
+            .map(|(field, _)| field.to_string())
+            .collect()
+    }
+
+    /// Authorize each field of `input` with its own scope, see
+    /// [`Authorizor::authorize_group`](struct.Authorizor.html#method.authorize_group).
+    ///
+    /// The default implementation runs
+    /// [`filter_unauthorized_fields_with_map`](#method.filter_unauthorized_fields_with_map)
+    /// then [`builder_authorized_struct`](#tymethod.builder_authorized_struct). Collections
+    /// authorize each item.
+    ///
+    /// # Errors
+    ///
+    fn authorize_group(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        let unauthorized_fields = Self::filter_unauthorized_fields_with_map(input, field_scopes);
+        let authorized = Self::builder_authorized_struct(input, &unauthorized_fields)?;
+
+        Ok(AuthorizedResult {
+            input_scope: Scope::default(),
+            inner: authorized,
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: mask_sensitive_fields::<Self>(unauthorized_fields),
+        })
+    }
+
+    /// Authorize `input` with each scope of `scopes` in turn, see
+    /// [`Authorizor::authorize_chain`](struct.Authorizor.html#method.authorize_chain).
+    ///
+    /// The default implementation runs [`authorize`](#tymethod.authorize) and
+    /// [`filter_unauthorized_fields`](#tymethod.filter_unauthorized_fields) for each step, then
+    /// builds the structure once. `#[derive(Authorized)]` only filters the fields and checks
+    /// its global scope at each step. Collections authorize each item with the chain.
+    ///
+    /// # Errors
+    ///
+    fn authorize_chain(
+        input: &Self,
+        scopes: &[Scope],
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        authorize_steps(input, scopes, |scope| {
+            let status = Self::authorize(input, scope)?.status;
+
+            Ok((status, Self::filter_unauthorized_fields(input, scope)))
+        })
+    }
+
+    /// Same as [`builder_authorized_struct`](#tymethod.builder_authorized_struct) but fields
+    /// whose `default` function panics fall back to `Default::default()` instead of aborting
+    /// the build. See the [`fallible`](fallible/index.html) module.
+    ///
+    /// # Errors
+    ///
+    fn authorize_recover<S: std::cmp::PartialEq + AsRef<str>>(
+        input: &Self,
+        unauthorized_fields: &[S],
+    ) -> Result<(Self::Authorized, Vec<FieldBuildError>), AuthorizedError> {
+        let guard = RecoverGuard::register();
+        let authorized = Self::builder_authorized_struct(input, unauthorized_fields)?;
+
+        Ok((authorized, guard.into_errors()))
+    }
+
     ///
     /// # Errors
     ///
@@ -53,9 +411,48 @@ pub trait Authorizable {
 
 /// Authorizor exposed mthods to help you authorize structures which implement
 /// [Authorizable](trait.Authorizable.html) trait.
+///
+/// Methods are associated functions, an `Authorizor` value is only needed to register an audit
+/// callback with [`with_field_audit`](#method.with_field_audit).
+#[derive(Default)]
 pub struct Authorizor {}
 
 impl Authorizor {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a callback called for every field decision taken by
+    /// [`AuditedAuthorizor::authorize_audited`](audit/struct.AuditedAuthorizor.html#method.authorize_audited).
+    #[must_use]
+    pub fn with_field_audit<F: Fn(FieldAuditEvent) + 'static>(
+        self,
+        callback: F,
+    ) -> AuditedAuthorizor {
+        AuditedAuthorizor::new(Rc::new(callback))
+    }
+
+    /// Explain the global and per-field decisions an authorization of `inner` with `scope`
+    /// takes, without building the authorized value.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the scope parsing.
+    pub fn explain<A: Authorizable, T: IntoScope>(
+        inner: &A,
+        scope: &T,
+    ) -> Result<AuthorizationExplanation, ParseScopeErr> {
+        Ok(AuthorizationExplanation::new(inner, &scope.into_scope()?))
+    }
+
+    /// The scopes required by `A`, without authorizing anything. Useful to document the scopes
+    /// of an API.
+    #[must_use]
+    pub fn build_scope_for<A: Authorizable>() -> ScopeRequirements {
+        A::scope_requirements()
+    }
+
     /// Create an authorized version of the input structure validated by the scope implementing
     /// [`IntoScope`](scope/trait.IntoScope.html).
     ///
@@ -64,139 +461,1121 @@ impl Authorizor {
     ///
     /// # Errors
     ///
-    pub fn authorize<A: Authorizable, T: IntoScope>(
+    pub fn authorize<A: Authorizable, T: IntoScope + ?Sized>(
         inner: &A,
         scope: &T,
     ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
-        let scope: Scope = scope.into_scope()?;
-
-        A::authorize(inner, &scope)
-    }
-}
+        let result = scope
+            .into_scope()
+            .map_err(AuthorizedError::from)
+            .and_then(|scope| A::authorize(inner, &scope));
 
-impl<T> Authorizable for Vec<T>
-where
-    T: Authorizable,
-{
-    type Authorized = Vec<AuthorizedResult<T::Authorized>>;
+        #[cfg(feature = "with_log")]
+        Self::log_result(&result);
 
-    fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
-        _input: &Self,
-        _unauthorized_fields: &[S],
-    ) -> Result<Self::Authorized, AuthorizedError> {
-        Ok(vec![])
+        result
     }
 
-    fn filter_unauthorized_fields(_input: &Self, _scope: &Scope) -> UnAuthorizedFields {
-        vec![]
+    /// Parse `scope` and defer the authorization of `inner` until
+    /// [`LazyAuthorizedResult::materialize`](struct.LazyAuthorizedResult.html#method.materialize)
+    /// is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the scope parsing.
+    pub fn authorize_lazy<'a, A: Authorizable, T: IntoScope>(
+        inner: &'a A,
+        scope: &T,
+    ) -> Result<LazyAuthorizedResult<'a, A>, ParseScopeErr> {
+        Ok(LazyAuthorizedResult::new(inner, scope.into_scope()?))
     }
 
-    fn authorize(
-        input: &Self,
-        authorizer: &Scope,
-    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
-        let (inner, _errors): (Vec<Result<AuthorizedResult<_>, AuthorizedError>>, Vec<_>) = input
-            .iter()
-            .map(|v| Authorizable::authorize(v, authorizer))
-            .partition(Result::is_ok);
+    /// Parse `scope` then authorize the output of `future` once it resolves.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the scope parsing. The returned future fails like
+    /// [`authorize`](#method.authorize).
+    #[cfg(feature = "with_async")]
+    pub fn authorize_future<F, I, T>(
+        future: F,
+        scope: &T,
+    ) -> Result<
+        impl std::future::Future<Output = Result<AuthorizedResult<I::Authorized>, AuthorizedError>>,
+        ParseScopeErr,
+    >
+    where
+        F: std::future::Future<Output = I>,
+        I: Authorizable,
+        T: IntoScope,
+    {
+        let scope = scope.into_scope()?;
 
-        let inner: Self::Authorized = inner.into_iter().filter_map(Result::ok).collect();
-        Ok(AuthorizedResult {
-            inner,
-            input_scope: authorizer.clone(),
-            status: AuthorizationStatus::Authorized,
-            unauthorized_fields: vec![],
-        })
+        Ok(async move { I::authorize(&future.await, &scope) })
     }
-}
 
-impl<T> Authorizable for &T
-where
-    T: Authorizable,
-{
-    type Authorized = T::Authorized;
+    /// The fields of `inner` which are unauthorized for `scope`, without building the
+    /// authorized value nor checking the status of the structure.
+    ///
+    /// # Errors
+    ///
+    pub fn unauthorized_fields_for<A: Authorizable, T: IntoScope>(
+        inner: &A,
+        scope: &T,
+    ) -> Result<UnAuthorizedFields, AuthorizedError> {
+        let scope: Scope = scope.into_scope()?;
 
-    fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
-        _input: &Self,
-        _unauthorized_fields: &[S],
-    ) -> Result<Self::Authorized, AuthorizedError> {
-        unreachable!();
+        Ok(mask_sensitive_fields::<A>(A::filter_unauthorized_fields(
+            inner, &scope,
+        )))
     }
 
-    fn filter_unauthorized_fields(_input: &Self, _scope: &Scope) -> UnAuthorizedFields {
-        unreachable!();
+    /// Same as [`authorize`](#method.authorize) with `default_scope` when `scope` is `None`, e.g.
+    /// when an optional header is missing.
+    ///
+    /// # Errors
+    ///
+    pub fn authorize_with_default<A: Authorizable, T: IntoScope>(
+        inner: &A,
+        scope: Option<T>,
+        default_scope: T,
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        Self::authorize(inner, &scope.unwrap_or(default_scope))
     }
 
-    fn authorize(
-        input: &Self,
-        authorizer: &Scope,
-    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
-        let unauthorized_fields: Vec<String> = T::filter_unauthorized_fields(input, authorizer);
-        let inner = T::builder_authorized_struct(input, &unauthorized_fields)?;
-
-        Ok(AuthorizedResult {
-            inner,
-            input_scope: authorizer.clone(),
-            status: AuthorizationStatus::Authorized,
-            unauthorized_fields,
-        })
+    /// Same as [`authorize`](#method.authorize) with an empty scope when `scope` is `None`.
+    ///
+    /// # Errors
+    ///
+    pub fn authorize_with_empty_scope_fallback<A: Authorizable, T: IntoScope>(
+        inner: &A,
+        scope: Option<T>,
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        match scope {
+            Some(scope) => Self::authorize(inner, &scope),
+            None => Self::authorize(inner, &Scope::default()),
+        }
     }
-}
-
-pub trait Authorized {}
 
-impl<T> Authorized for AuthorizedResult<T> where T: Authorized {}
-impl<T> Authorized for Vec<T> where T: Authorized {}
+    /// Reports an authorization with `log`: `debug` for every result, `warn` for `UnAuthorized`
+    /// results and `error` for errors.
+    #[cfg(feature = "with_log")]
+    fn log_result<T>(result: &Result<AuthorizedResult<T>, AuthorizedError>) {
+        const TARGET: &str = "authorized::authorizor";
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        match result {
+            Ok(result) => {
+                log::debug!(
+                    target: TARGET,
+                    "scope: `{}`, status: {:?}, unauthorized fields: {}",
+                    result.input_scope,
+                    result.status,
+                    result.unauthorized_fields.len()
+                );
 
-    #[derive(Debug)]
-    struct MyUser {
-        name: String,
-        pass: String,
-        email: String,
+                if result.status == AuthorizationStatus::UnAuthorized {
+                    log::warn!(target: TARGET, "scope `{}` is unauthorized", result.input_scope);
+                }
+            }
+            Err(error) => log::error!(target: TARGET, "authorization failed: {error}"),
+        }
     }
 
-    impl Authorized for MyUser {}
+    /// Parse `scope` and check that it only grants `allowed_scopes` tokens before authorizing
+    /// `inner` with it. See [`ScopeWhitelist`](whitelist/struct.ScopeWhitelist.html).
+    ///
+    /// # Errors
+    ///
+    /// Returns `AuthorizedError::DisallowedScope` if `scope` grants a token which isn't in
+    /// `allowed_scopes`.
+    pub fn validate_and_authorize<A: Authorizable>(
+        inner: &A,
+        scope: &str,
+        allowed_scopes: &[&str],
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        let scope = scope.parse::<Scope>()?;
+        ScopeWhitelist::new(allowed_scopes).validate(&scope)?;
 
-    impl Authorizable for MyUser {
-        type Authorized = Self;
+        A::authorize(inner, &scope)
+    }
 
-        fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
-            input: &Self,
-            _unauthorized_fields: &[S],
-        ) -> Result<Self::Authorized, AuthorizedError> {
-            Ok(Self {
-                name: input.name.clone(),
-                pass: input.pass.clone(),
-                email: String::new(),
+    /// Same as [`authorize`](#method.authorize) but only returns the authorized value when the
+    /// structure is authorized and no field has been redacted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AuthorizedError::InsufficientScope` if the structure isn't authorized or if a
+    /// field is unauthorized.
+    pub fn authorize_strict<A: Authorizable, T: IntoScope>(
+        inner: &A,
+        scope: &T,
+    ) -> Result<A::Authorized, AuthorizedError> {
+        let result = Self::authorize(inner, scope)?;
+
+        if result.unauthorized_fields.is_empty() && result.status == AuthorizationStatus::Authorized
+        {
+            Ok(result.inner)
+        } else {
+            Err(AuthorizedError::InsufficientScope {
+                required_fields: result.unauthorized_fields,
+                provided_scope: Box::new(result.input_scope),
             })
         }
+    }
 
-        fn filter_unauthorized_fields(_input: &Self, _scope: &Scope) -> UnAuthorizedFields {
-            vec!["email".into()]
+    /// Same as [`authorize`](#method.authorize) but fails when the structure is unauthorized,
+    /// so an unauthorized access can be propagated with `?`. Redacted fields are accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AuthorizedError::UnAuthorized` if the result status is `UnAuthorized`.
+    pub fn authorize_or_unauthorized<A: Authorizable, T: IntoScope>(
+        inner: &A,
+        scope: &T,
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        let result = Self::authorize(inner, scope)?;
+
+        match result.status {
+            AuthorizationStatus::Authorized => Ok(result),
+            AuthorizationStatus::UnAuthorized => Err(UnauthorizedResult {
+                input_scope: result.input_scope,
+                struct_type: std::any::type_name::<A>(),
+            }
+            .into()),
         }
+    }
 
-        fn authorize(
-            input: &Self,
-            authorizer: &Scope,
-        ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
-            let unauthorized_fields = Self::filter_unauthorized_fields(input, authorizer);
-            let inner = Self::builder_authorized_struct(input, &unauthorized_fields)?;
+    /// Authorize every item of `items`, failing fast instead of skipping the failing items like
+    /// the `Vec<T>` implementation of [`Authorizable`](trait.Authorizable.html) does. The
+    /// remaining items aren't processed after a failure.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the scope parsing or the first error of
+    /// [`Authorizable::authorize`](trait.Authorizable.html#tymethod.authorize), and
+    /// `AuthorizedError::ItemUnauthorized` with the index of the first item whose status is
+    /// `UnAuthorized`.
+    pub fn authorize_many_strict<A: Authorizable, T: IntoScope>(
+        items: &[A],
+        scope: &T,
+    ) -> Result<Vec<AuthorizedResult<A::Authorized>>, AuthorizedError> {
+        let scope = scope.into_scope()?;
 
-            Ok(AuthorizedResult {
-                inner,
-                input_scope: authorizer.clone(),
-                status: AuthorizationStatus::Authorized,
-                unauthorized_fields,
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let result = A::authorize(item, &scope)?;
+
+                match result.status {
+                    AuthorizationStatus::Authorized => Ok(result),
+                    AuthorizationStatus::UnAuthorized => Err(AuthorizedError::ItemUnauthorized {
+                        index,
+                        inner: Box::new(
+                            UnauthorizedResult {
+                                input_scope: result.input_scope,
+                                struct_type: std::any::type_name::<A>(),
+                            }
+                            .into(),
+                        ),
+                    }),
+                }
             })
-        }
+            .collect()
+    }
+
+    /// Authorize `inner` with each scope of `scopes` in turn, e.g. a service scope then a
+    /// resource scope. Unlike authorizing with each scope independently the redactions add up:
+    /// a field unauthorized at one step stays unauthorized, and the structure is `UnAuthorized`
+    /// if any step is.
+    ///
+    /// The `input_scope` of the result is the last scope of the chain. An empty chain
+    /// authorizes `inner` with an empty scope. Collections authorize each item with the chain,
+    /// see [`Authorizable::authorize_chain`](trait.Authorizable.html#method.authorize_chain).
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of a step or of the build of the structure.
+    pub fn authorize_chain<A: Authorizable>(
+        inner: &A,
+        scopes: &[Scope],
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        A::authorize_chain(inner, scopes)
+    }
+
+    /// Same as [`authorize`](#method.authorize) but fails when a field is unauthorized. Unlike
+    /// [`authorize_strict`](#method.authorize_strict) the status of the structure isn't checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AuthorizedError::PartiallyUnauthorized` with the scope required by the
+    /// unauthorized fields if a field is unauthorized.
+    pub fn authorize_all_fields<A: Authorizable, T: IntoScope>(
+        inner: &A,
+        scope: &T,
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        let result = Self::authorize(inner, scope)?;
+
+        if result.unauthorized_fields.is_empty() {
+            return Ok(result);
+        }
+
+        // Sensitive fields are masked in the result, their scope is looked up by their real name.
+        let unauthorized_sensitive_fields = A::field_names().iter().filter(|field| {
+            A::is_sensitive_field(field)
+                && !A::is_authorized_for_field(inner, field, &result.input_scope)
+        });
+        let required_scope = result
+            .unauthorized_fields
+            .iter()
+            .map(String::as_str)
+            .chain(unauthorized_sensitive_fields.copied())
+            .filter_map(A::field_scope)
+            .filter_map(|scope| scope.parse::<Scope>().ok())
+            .fold(Scope::default(), |required, scope| required.union(&scope));
+
+        Err(AuthorizedError::PartiallyUnauthorized {
+            struct_type: std::any::type_name::<A>(),
+            unauthorized_fields: result.unauthorized_fields,
+            scope: Box::new(required_scope),
+        })
+    }
+
+    /// Same as [`authorize`](#method.authorize) but logs the outcome with `tracing`:
+    ///
+    /// - `info` with the scope, the status and the number of unauthorized fields on success,
+    /// - `warn` when the result is `UnAuthorized`,
+    /// - `error` when the authorization fails.
+    ///
+    /// # Errors
+    ///
+    #[cfg(feature = "with_tracing")]
+    pub fn authorize_and_log<A: Authorizable, T: IntoScope + std::fmt::Debug>(
+        inner: &A,
+        scope: &T,
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        match Self::authorize(inner, scope) {
+            Ok(result) => {
+                if result.status == AuthorizationStatus::UnAuthorized {
+                    tracing::warn!(
+                        ?scope,
+                        status = ?result.status,
+                        unauthorized_fields = result.unauthorized_fields.len(),
+                        "unauthorized"
+                    );
+                } else {
+                    tracing::info!(
+                        ?scope,
+                        status = ?result.status,
+                        unauthorized_fields = result.unauthorized_fields.len(),
+                        "authorized"
+                    );
+                }
+
+                Ok(result)
+            }
+            Err(error) => {
+                tracing::error!(?scope, ?error, "authorization failed");
+
+                Err(error)
+            }
+        }
+    }
+
+    /// Same as [`authorize`](#method.authorize) but the unauthorized fields whose `default`
+    /// function panics fall back to `Default::default()`. The caught panics are returned along
+    /// with the result.
+    ///
+    /// # Errors
+    ///
+    pub fn authorize_fallible_fields<A: Authorizable, T: IntoScope>(
+        inner: &A,
+        scope: &T,
+    ) -> Result<(AuthorizedResult<A::Authorized>, Vec<FieldBuildError>), AuthorizedError> {
+        let guard = RecoverGuard::register();
+        let result = Self::authorize(inner, scope)?;
+
+        Ok((result, guard.into_errors()))
+    }
+
+    /// Authorize each field of `inner` with its own scope, see
+    /// [`Authorizable::filter_unauthorized_fields_with_map`](trait.Authorizable.html#method.filter_unauthorized_fields_with_map).
+    ///
+    /// As there is no single input scope, the global scope of the structure isn't checked: the
+    /// result is always `Authorized` and its `input_scope` is empty.
+    ///
+    /// # Errors
+    ///
+    pub fn authorize_group<A: Authorizable>(
+        inner: &A,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        A::authorize_group(inner, field_scopes)
+    }
+
+    /// Same as [`authorize`](#method.authorize) but takes an already parsed
+    /// [`Scope`](scope/struct.Scope.html), avoiding the parsing and the clone done by
+    /// [`IntoScope`](scope/trait.IntoScope.html) on every call.
+    ///
+    /// # Errors
+    ///
+    pub fn authorize_ref<A: Authorizable>(
+        inner: &A,
+        scope: &Scope,
+    ) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        let result = A::authorize(inner, scope);
+
+        #[cfg(feature = "with_log")]
+        Self::log_result(&result);
+
+        result
+    }
+}
+
+impl<T> Authorizable for Vec<T>
+where
+    T: Authorizable,
+{
+    type Authorized = Vec<AuthorizedResult<T::Authorized>>;
+
+    fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+        input: &Self,
+        unauthorized_fields: &[S],
+    ) -> Result<Self::Authorized, AuthorizedError> {
+        Authorizable::builder_authorized_struct(&input.as_slice(), unauthorized_fields)
+    }
+
+    fn filter_unauthorized_fields(input: &Self, scope: &Scope) -> UnAuthorizedFields {
+        Authorizable::filter_unauthorized_fields(&input.as_slice(), scope)
+    }
+
+    fn filter_unauthorized_fields_with_map(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> UnAuthorizedFields {
+        Authorizable::filter_unauthorized_fields_with_map(&input.as_slice(), field_scopes)
+    }
+
+    fn authorize_group(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        Authorizable::authorize_group(&input.as_slice(), field_scopes)
+    }
+
+    fn authorize_chain(
+        input: &Self,
+        scopes: &[Scope],
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        Authorizable::authorize_chain(&input.as_slice(), scopes)
+    }
+
+    fn authorize(
+        input: &Self,
+        authorizer: &Scope,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        Authorizable::authorize(&input.as_slice(), authorizer)
+    }
+}
+
+/// Builds `item`, the `index`-th item of a collection, with the unauthorized fields prefixed by
+/// its index (`0.email`), like the items of a tuple. Without a scope the item is `Authorized`
+/// and its `input_scope` is empty.
+fn build_item<T: Authorizable, S: AsRef<str>>(
+    index: usize,
+    item: &T,
+    unauthorized_fields: &[S],
+) -> Result<AuthorizedResult<T::Authorized>, AuthorizedError> {
+    let prefix = format!("{index}.");
+    let fields: Vec<&str> = unauthorized_fields
+        .iter()
+        .filter_map(|field| field.as_ref().strip_prefix(prefix.as_str()))
+        .collect();
+
+    Ok(AuthorizedResult {
+        input_scope: Scope::default(),
+        inner: T::builder_authorized_struct(item, &fields)?,
+        status: AuthorizationStatus::Authorized,
+        unauthorized_fields: mask_sensitive_fields::<T>(
+            fields.into_iter().map(String::from).collect(),
+        ),
+    })
+}
+
+/// Collects the items built from the items of an array of length `N`.
+fn collect_array<T, const N: usize>(items: Vec<T>) -> [T; N] {
+    match items.try_into() {
+        Ok(items) => items,
+        Err(_) => unreachable!("one item is built for each item of the array"),
+    }
+}
+
+impl<T> Authorizable for &[T]
+where
+    T: Authorizable,
+{
+    type Authorized = Vec<AuthorizedResult<T::Authorized>>;
+
+    fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+        input: &Self,
+        unauthorized_fields: &[S],
+    ) -> Result<Self::Authorized, AuthorizedError> {
+        input
+            .iter()
+            .enumerate()
+            .map(|(index, item)| build_item(index, item, unauthorized_fields))
+            .collect()
+    }
+
+    fn filter_unauthorized_fields(input: &Self, scope: &Scope) -> UnAuthorizedFields {
+        input
+            .iter()
+            .enumerate()
+            .flat_map(|(index, item)| {
+                T::filter_unauthorized_fields(item, scope)
+                    .into_iter()
+                    .map(move |field| format!("{index}.{field}"))
+            })
+            .collect()
+    }
+
+    fn filter_unauthorized_fields_with_map(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> UnAuthorizedFields {
+        input
+            .iter()
+            .enumerate()
+            .flat_map(|(index, item)| {
+                T::filter_unauthorized_fields_with_map(item, field_scopes)
+                    .into_iter()
+                    .map(move |field| format!("{index}.{field}"))
+            })
+            .collect()
+    }
+
+    /// Like `authorize`, the items which can't be authorized are skipped.
+    fn authorize_group(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        Ok(AuthorizedResult {
+            inner: input
+                .iter()
+                .filter_map(|item| T::authorize_group(item, field_scopes).ok())
+                .collect(),
+            input_scope: Scope::default(),
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: vec![],
+        })
+    }
+
+    /// Like `authorize`, the items which can't be authorized are skipped.
+    fn authorize_chain(
+        input: &Self,
+        scopes: &[Scope],
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        Ok(AuthorizedResult {
+            inner: input
+                .iter()
+                .filter_map(|item| T::authorize_chain(item, scopes).ok())
+                .collect(),
+            input_scope: scopes.last().cloned().unwrap_or_default(),
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: vec![],
+        })
+    }
+
+    fn authorize(
+        input: &Self,
+        authorizer: &Scope,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        let (inner, _errors): (Vec<Result<AuthorizedResult<_>, AuthorizedError>>, Vec<_>) = input
+            .iter()
+            .map(|v| Authorizable::authorize(v, authorizer))
+            .partition(Result::is_ok);
+
+        let inner: Self::Authorized = inner.into_iter().filter_map(Result::ok).collect();
+        Ok(AuthorizedResult {
+            inner,
+            input_scope: authorizer.clone(),
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: vec![],
+        })
+    }
+}
+
+/// Unlike `Vec<T>` and `&[T]`, the authorized array must keep its length: the first error
+/// returned by an item is propagated.
+impl<T, const N: usize> Authorizable for [T; N]
+where
+    T: Authorizable,
+{
+    type Authorized = [AuthorizedResult<T::Authorized>; N];
+
+    fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+        input: &Self,
+        unauthorized_fields: &[S],
+    ) -> Result<Self::Authorized, AuthorizedError> {
+        Authorizable::builder_authorized_struct(&input.as_slice(), unauthorized_fields)
+            .map(collect_array)
+    }
+
+    fn filter_unauthorized_fields(input: &Self, scope: &Scope) -> UnAuthorizedFields {
+        Authorizable::filter_unauthorized_fields(&input.as_slice(), scope)
+    }
+
+    fn filter_unauthorized_fields_with_map(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> UnAuthorizedFields {
+        Authorizable::filter_unauthorized_fields_with_map(&input.as_slice(), field_scopes)
+    }
+
+    fn authorize_group(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        let inner = input
+            .iter()
+            .map(|item| T::authorize_group(item, field_scopes))
+            .collect::<Result<_, _>>()?;
+
+        Ok(AuthorizedResult {
+            inner: collect_array(inner),
+            input_scope: Scope::default(),
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: vec![],
+        })
+    }
+
+    fn authorize_chain(
+        input: &Self,
+        scopes: &[Scope],
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        let inner = input
+            .iter()
+            .map(|item| T::authorize_chain(item, scopes))
+            .collect::<Result<_, _>>()?;
+
+        Ok(AuthorizedResult {
+            inner: collect_array(inner),
+            input_scope: scopes.last().cloned().unwrap_or_default(),
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: vec![],
+        })
+    }
+
+    fn authorize(
+        input: &Self,
+        authorizer: &Scope,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        let inner: Vec<AuthorizedResult<T::Authorized>> = input
+            .iter()
+            .map(|v| Authorizable::authorize(v, authorizer))
+            .collect::<Result<_, _>>()?;
+
+        Ok(AuthorizedResult {
+            inner: collect_array(inner),
+            input_scope: authorizer.clone(),
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: vec![],
+        })
+    }
+}
+
+impl<T> Authorizable for &T
+where
+    T: Authorizable,
+{
+    type Authorized = T::Authorized;
+
+    fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+        input: &Self,
+        unauthorized_fields: &[S],
+    ) -> Result<Self::Authorized, AuthorizedError> {
+        T::builder_authorized_struct(input, unauthorized_fields)
+    }
+
+    fn filter_unauthorized_fields(input: &Self, scope: &Scope) -> UnAuthorizedFields {
+        T::filter_unauthorized_fields(input, scope)
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        T::field_names()
+    }
+
+    fn scoped_field_names() -> &'static [(&'static str, Option<&'static str>)] {
+        T::scoped_field_names()
+    }
+
+    fn field_scope(field_name: &str) -> Option<&'static str> {
+        T::field_scope(field_name)
+    }
+
+    fn is_sensitive_field(field_name: &str) -> bool {
+        T::is_sensitive_field(field_name)
+    }
+
+    fn field_expiries() -> &'static [(&'static str, u64)] {
+        T::field_expiries()
+    }
+
+    fn scope_requirements() -> ScopeRequirements {
+        T::scope_requirements()
+    }
+
+    fn is_authorized_for_field(input: &Self, field: &str, scope: &Scope) -> bool {
+        T::is_authorized_for_field(input, field, scope)
+    }
+
+    fn filter_unauthorized_fields_with_map(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> UnAuthorizedFields {
+        T::filter_unauthorized_fields_with_map(input, field_scopes)
+    }
+
+    fn authorize_group(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        T::authorize_group(input, field_scopes)
+    }
+
+    fn authorize_chain(
+        input: &Self,
+        scopes: &[Scope],
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        T::authorize_chain(input, scopes)
+    }
+
+    fn authorize(
+        input: &Self,
+        authorizer: &Scope,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        let unauthorized_fields: Vec<String> = T::filter_unauthorized_fields(input, authorizer);
+        let inner = T::builder_authorized_struct(input, &unauthorized_fields)?;
+
+        Ok(AuthorizedResult {
+            inner,
+            input_scope: authorizer.clone(),
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: mask_sensitive_fields::<T>(unauthorized_fields),
+        })
+    }
+}
+
+impl<T> Authorizable for Cow<'_, T>
+where
+    T: Authorizable + Clone,
+{
+    type Authorized = T::Authorized;
+
+    fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+        input: &Self,
+        unauthorized_fields: &[S],
+    ) -> Result<Self::Authorized, AuthorizedError> {
+        T::builder_authorized_struct(input.as_ref(), unauthorized_fields)
+    }
+
+    fn filter_unauthorized_fields(input: &Self, scope: &Scope) -> UnAuthorizedFields {
+        T::filter_unauthorized_fields(input.as_ref(), scope)
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        T::field_names()
+    }
+
+    fn scoped_field_names() -> &'static [(&'static str, Option<&'static str>)] {
+        T::scoped_field_names()
+    }
+
+    fn field_scope(field_name: &str) -> Option<&'static str> {
+        T::field_scope(field_name)
+    }
+
+    fn is_sensitive_field(field_name: &str) -> bool {
+        T::is_sensitive_field(field_name)
+    }
+
+    fn field_expiries() -> &'static [(&'static str, u64)] {
+        T::field_expiries()
+    }
+
+    fn scope_requirements() -> ScopeRequirements {
+        T::scope_requirements()
+    }
+
+    fn filter_unauthorized_fields_with_map(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> UnAuthorizedFields {
+        T::filter_unauthorized_fields_with_map(input.as_ref(), field_scopes)
+    }
+
+    fn authorize_group(
+        input: &Self,
+        field_scopes: &HashMap<&str, Scope>,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        T::authorize_group(input.as_ref(), field_scopes)
+    }
+
+    fn authorize_chain(
+        input: &Self,
+        scopes: &[Scope],
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        T::authorize_chain(input.as_ref(), scopes)
+    }
+
+    fn authorize(
+        input: &Self,
+        authorizer: &Scope,
+    ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+        T::authorize(input.as_ref(), authorizer)
+    }
+}
+
+/// Authorizes every item of the tuple with the same scope. Unauthorized fields are prefixed
+/// with the index of their item (`0.email`) and the result is `UnAuthorized` if any item is.
+macro_rules! tuple_authorizable {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: Authorizable),+> Authorizable for ($($name,)+) {
+            type Authorized = ($($name::Authorized,)+);
+
+            fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+                input: &Self,
+                unauthorized_fields: &[S],
+            ) -> Result<Self::Authorized, AuthorizedError> {
+                Ok(($(
+                    $name::builder_authorized_struct(
+                        &input.$index,
+                        &unauthorized_fields
+                            .iter()
+                            .filter_map(|field| {
+                                field.as_ref().strip_prefix(concat!(stringify!($index), "."))
+                            })
+                            .collect::<Vec<&str>>(),
+                    )?,
+                )+))
+            }
+
+            fn filter_unauthorized_fields(input: &Self, scope: &Scope) -> UnAuthorizedFields {
+                let mut unauthorized_fields = vec![];
+                $(
+                    unauthorized_fields.extend(
+                        $name::filter_unauthorized_fields(&input.$index, scope)
+                            .into_iter()
+                            .map(|field| format!(concat!(stringify!($index), ".{}"), field)),
+                    );
+                )+
+
+                unauthorized_fields
+            }
+
+            fn authorize(
+                input: &Self,
+                authorizer: &Scope,
+            ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+                let mut status = AuthorizationStatus::Authorized;
+                let mut unauthorized_fields = vec![];
+
+                let inner = ($({
+                    let result = $name::authorize(&input.$index, authorizer)?;
+                    if result.status == AuthorizationStatus::UnAuthorized {
+                        status = AuthorizationStatus::UnAuthorized;
+                    }
+                    unauthorized_fields.extend(
+                        result
+                            .unauthorized_fields
+                            .into_iter()
+                            .map(|field| format!(concat!(stringify!($index), ".{}"), field)),
+                    );
+
+                    result.inner
+                },)+);
+
+                Ok(AuthorizedResult {
+                    input_scope: authorizer.clone(),
+                    inner,
+                    status,
+                    unauthorized_fields,
+                })
+            }
+        }
+
+        impl<$($name: Authorized),+> Authorized for ($($name,)+) {}
+    };
+}
+
+tuple_authorizable!(A 0, B 1);
+tuple_authorizable!(A 0, B 1, C 2);
+
+/// Marks the types that can be authorized.
+///
+/// The `#[authorized(...)]` attributes of `#[derive(Authorized)]` are checked while the macro is
+/// expanded, a misspelled option like `sensitve` is reported as an "Unknown field" compile
+/// error on the option:
+///
+/// ```compile_fail
+/// use authorized::prelude::*;
+///
+/// #[derive(Authorized)]
+/// struct User {
+///     #[authorized(scope = "admin", sensitve)]
+///     password: String,
+/// }
+/// ```
+pub trait Authorized {}
+
+impl<T> Authorized for AuthorizedResult<T> where T: Authorized {}
+impl<T> Authorized for Vec<T> where T: Authorized {}
+impl<T, const N: usize> Authorized for [T; N] where T: Authorized {}
+impl<T> Authorized for Cow<'_, T> where T: Authorized + Clone {}
+
+/// Implemented for every `Clone` type, only used to improve the error reported when a field
+/// of a `#[derive(Authorized)]` struct can't be cloned. Without a `default` function the error,
+/// "`SecretString` must implement `Clone` to be used as a field of a `#[derive(Authorized)]`
+/// struct", points to the type of the field:
+///
+/// ```compile_fail
+/// use authorized::prelude::*;
+///
+/// struct SecretString(String);
+///
+/// #[derive(Authorized)]
+/// struct User {
+///     #[authorized(scope = "admin")]
+///     password: SecretString,
+/// }
+/// ```
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` must implement `Clone` to be used as a field of a `#[derive(Authorized)]` struct",
+    label = "this field type doesn't implement `Clone`"
+)]
+pub trait AuthorizedField: Clone {}
+
+impl<T: Clone> AuthorizedField for T {}
+
+/// This is called by the code generated by `#[derive(Authorized)]`.
+#[doc(hidden)]
+pub fn assert_authorized_field<T: AuthorizedField>() {}
+
+/// Builds the authorized value of a `#[derive(Authorized)]` struct.
+///
+/// [`BuildWithClone`](trait.BuildWithClone.html) is picked when the struct implements `Clone`:
+/// the struct is cloned once and its unauthorized fields are overridden. Otherwise
+/// [`BuildFromFields`](trait.BuildFromFields.html) builds the struct field by field.
+///
+/// This is called by the code generated by `#[derive(Authorized)]`.
+#[doc(hidden)]
+pub struct StructBuilder<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait BuildWithClone {
+    type Struct;
+
+    fn build<C, F>(&self, with_clone: C, from_fields: F) -> Self::Struct
+    where
+        C: FnOnce(Self::Struct) -> Self::Struct,
+        F: FnOnce() -> Self::Struct;
+}
+
+impl<T: Clone> BuildWithClone for StructBuilder<'_, T> {
+    type Struct = T;
+
+    fn build<C, F>(&self, with_clone: C, _from_fields: F) -> T
+    where
+        C: FnOnce(T) -> T,
+        F: FnOnce() -> T,
+    {
+        with_clone(self.0.clone())
+    }
+}
+
+#[doc(hidden)]
+pub trait BuildFromFields {
+    type Struct;
+
+    fn build<C, F>(&self, with_clone: C, from_fields: F) -> Self::Struct
+    where
+        C: FnOnce(Self::Struct) -> Self::Struct,
+        F: FnOnce() -> Self::Struct;
+}
+
+impl<T> BuildFromFields for &StructBuilder<'_, T> {
+    type Struct = T;
+
+    fn build<C, F>(&self, _with_clone: C, from_fields: F) -> T
+    where
+        C: FnOnce(T) -> T,
+        F: FnOnce() -> T,
+    {
+        from_fields()
+    }
+}
+
+/// Asserts that the `default` function of a field builds a value of the field type, and that
+/// the field type implements `Clone`. Both errors point to the `default` attribute.
+///
+/// A function building another type fails with "expected `hidden` to return `String`, but it
+/// returns `u32`":
+///
+/// ```compile_fail
+/// use authorized::prelude::*;
+///
+/// fn hidden() -> u32 {
+///     0
+/// }
+///
+/// #[derive(Authorized)]
+/// struct User {
+///     #[authorized(scope = "admin", default = "hidden")]
+///     password: String,
+/// }
+/// ```
+///
+/// A field type that doesn't implement `Clone` fails with "`SecretString` must implement `Clone`
+/// to be used as a field of a `#[derive(Authorized)]` struct":
+///
+/// ```compile_fail
+/// use authorized::prelude::*;
+///
+/// #[derive(Default)]
+/// struct SecretString(String);
+///
+/// fn hidden() -> SecretString {
+///     SecretString("hidden".into())
+/// }
+///
+/// #[derive(Authorized)]
+/// struct User {
+///     #[authorized(scope = "admin", default = "hidden")]
+///     password: SecretString,
+/// }
+/// ```
+///
+/// A field can't have both a `default` function and `or_default`, the error points to the
+/// `default` attribute:
+///
+/// ```compile_fail
+/// use authorized::prelude::*;
+///
+/// fn hidden() -> String {
+///     "hidden".into()
+/// }
+///
+/// #[derive(Authorized)]
+/// struct User {
+///     #[authorized(scope = "admin", default = "hidden", or_default)]
+///     password: String,
+/// }
+/// ```
+///
+/// A `default` that isn't a path is reported once, on the `default` attribute, with
+/// "Invalid default path":
+///
+/// ```compile_fail
+/// use authorized::prelude::*;
+///
+/// #[derive(Authorized)]
+/// struct User {
+///     #[authorized(scope = "admin", default = "not a path")]
+///     password: String,
+/// }
+/// ```
+///
+/// This is called by the code generated by `#[derive(Authorized)]`.
+#[doc(hidden)]
+pub fn assert_authorized_default<T: AuthorizedField, F: FnOnce() -> T>(_default: F) {}
+
+/// Checks if the `expiry` Unix timestamp of a field, the midnight after its last day, is past.
+///
+/// This is called by the code generated by `#[derive(Authorized)]`.
+#[doc(hidden)]
+#[must_use]
+pub fn is_expired(expiry: u64) -> bool {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .is_ok_and(|now| now.as_secs() >= expiry)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+
+    use super::*;
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Polls `future` until it is ready.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Arc::new(NoopWaker).into();
+        let mut context = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+
+        loop {
+            if let Poll::Ready(output) = Pin::as_mut(&mut future).poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[allow(dead_code)]
+    struct MyUser {
+        name: String,
+        pass: String,
+        email: String,
+    }
+
+    impl Authorized for MyUser {}
+
+    impl Authorizable for MyUser {
+        type Authorized = Self;
+
+        fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+            input: &Self,
+            _unauthorized_fields: &[S],
+        ) -> Result<Self::Authorized, AuthorizedError> {
+            Ok(Self {
+                name: input.name.clone(),
+                pass: input.pass.clone(),
+                email: String::new(),
+            })
+        }
+
+        fn filter_unauthorized_fields(_input: &Self, _scope: &Scope) -> UnAuthorizedFields {
+            vec!["email".into()]
+        }
+
+        fn authorize(
+            input: &Self,
+            authorizer: &Scope,
+        ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+            let unauthorized_fields = Self::filter_unauthorized_fields(input, authorizer);
+            let inner = Self::builder_authorized_struct(input, &unauthorized_fields)?;
+
+            Ok(AuthorizedResult {
+                inner,
+                input_scope: authorizer.clone(),
+                status: AuthorizationStatus::Authorized,
+                unauthorized_fields,
+            })
+        }
     }
 
     #[cfg(feature = "with_serde")]
     #[test]
+    #[allow(unused_variables, clippy::uninlined_format_args)]
     fn it_works() -> Result<(), AuthorizedError> {
         let based_user = MyUser {
             name: "name".into(),
@@ -204,18 +1583,1227 @@ mod tests {
             email: "email".into(),
         };
 
-        let based_user2 = MyUser {
-            name: "name2".into(),
+        let based_user2 = MyUser {
+            name: "name2".into(),
+            pass: "pass".into(),
+            email: "email".into(),
+        };
+
+        let res = Authorizor::authorize(&based_user, "read:user")?;
+
+        let users = vec![based_user, based_user2];
+        let res = Authorizor::authorize(&users, "read:user")?;
+
+        println!("{:#?}", res);
+        Ok(())
+    }
+
+    #[test]
+    fn cows_are_authorized_without_allocation() -> Result<(), AuthorizedError> {
+        let user = MyUser {
+            name: "name".into(),
+            pass: "pass".into(),
+            email: "email".into(),
+        };
+
+        let borrowed: Cow<MyUser> = Cow::Borrowed(&user);
+        let res = Authorizor::authorize(&borrowed, &"read:user")?;
+        assert_eq!(res.inner.name, "name");
+        assert_eq!(res.unauthorized_fields, vec!["email".to_string()]);
+
+        let owned: Cow<MyUser> = Cow::Owned(user.clone());
+        assert_eq!(Authorizor::authorize(&owned, &"read:user")?, res);
+
+        Ok(())
+    }
+
+    #[test]
+    fn slices_and_arrays_can_be_authorized() -> Result<(), AuthorizedError> {
+        let users = [
+            MyUser {
+                name: "name".into(),
+                pass: "pass".into(),
+                email: "email".into(),
+            },
+            MyUser {
+                name: "name2".into(),
+                pass: "pass".into(),
+                email: "email".into(),
+            },
+        ];
+
+        let res = Authorizor::authorize(&users, &"read:user")?;
+        assert_eq!(res.inner.len(), 2);
+        assert_eq!(res.inner[1].inner.name, "name2");
+        assert_eq!(res.inner[1].unauthorized_fields, vec!["email".to_string()]);
+
+        let slice: &[MyUser] = &users;
+        let res = Authorizor::authorize(&slice, &"read:user")?;
+        assert_eq!(res.inner.len(), 2);
+        assert_eq!(res.inner[0].inner.name, "name");
+        assert!(res.inner[0].inner.email.is_empty());
+
+        Ok(())
+    }
+
+    mod collections {
+        use crate::prelude::*;
+
+        #[derive(Debug, Clone, Authorized)]
+        struct Contact {
+            name: String,
+            #[authorized(scope = "admin")]
+            email: String,
+        }
+
+        impl Authorized for Contact {}
+
+        fn contact(name: &str) -> Contact {
+            Contact {
+                name: name.into(),
+                email: "email".into(),
+            }
+        }
+
+        #[test]
+        fn collections_are_built_item_by_item() -> Result<(), AuthorizedError> {
+            let contacts = [contact("name"), contact("name2")];
+
+            let built = <[Contact; 2]>::builder_authorized_struct(&contacts, &["1.email"])?;
+            assert_eq!(built[0].inner.email, "email");
+            assert!(built[0].unauthorized_fields.is_empty());
+            assert_eq!(built[1].inner.name, "name2");
+            assert!(built[1].inner.email.is_empty());
+            assert_eq!(built[1].unauthorized_fields, vec!["email".to_string()]);
+
+            let (built, errors) = Vec::authorize_recover(&contacts.to_vec(), &["0.email"])?;
+            assert_eq!(built.len(), 2);
+            assert!(built[0].inner.email.is_empty());
+            assert_eq!(built[1].inner.email, "email");
+            assert!(errors.is_empty());
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tuples_are_authorized_item_by_item() -> Result<(), AuthorizedError> {
+        let user = MyUser {
+            name: "name".into(),
+            pass: "pass".into(),
+            email: "email".into(),
+        };
+
+        let res = Authorizor::authorize(&(user.clone(), user.clone()), &"read:user")?;
+        assert_eq!(
+            res.unauthorized_fields,
+            vec!["0.email".to_string(), "1.email".to_string()]
+        );
+        assert_eq!(res.status, AuthorizationStatus::Authorized);
+        assert!(res.inner.0.email.is_empty());
+        assert_eq!(res.inner.1.name, "name");
+
+        let triple = (user.clone(), user.clone(), user);
+        let res = Authorizor::authorize(&triple, &"read:user")?;
+        assert_eq!(res.unauthorized_fields.len(), 3);
+        assert_eq!(res.unauthorized_fields[2], "2.email");
+        assert_eq!(
+            <(MyUser, MyUser, MyUser)>::filter_unauthorized_fields(&triple, &res.input_scope),
+            res.unauthorized_fields
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn lazy_authorizations_are_built_on_demand() -> Result<(), AuthorizedError> {
+        let user = MyUser {
+            name: "name".into(),
             pass: "pass".into(),
             email: "email".into(),
         };
 
-        let res = Authorizor::authorize(&based_user, "read:user")?;
+        let lazy = Authorizor::authorize_lazy(&user, &"read:user")?;
+        assert_eq!(lazy.scope(), &"read:user".parse::<Scope>().unwrap());
+        assert_eq!(
+            lazy.materialize()?,
+            Authorizor::authorize(&user, &"read:user")?
+        );
 
-        let users = vec![based_user, based_user2];
-        let res = Authorizor::authorize(&users, "read:user")?;
+        assert!(Authorizor::authorize_lazy(&user, &"read:\"user\"").is_err());
 
-        println!("{:#?}", res);
         Ok(())
     }
+
+    mod const_generic {
+        use crate::prelude::*;
+
+        #[derive(Debug, Authorized)]
+        struct Buffer<const N: usize> {
+            data: Vec<u8>,
+            #[authorized(scope = "admin")]
+            checksum: u64,
+        }
+
+        impl<const N: usize> Authorized for Buffer<N> {}
+
+        #[test]
+        fn const_generic_structs_are_authorized() -> Result<(), AuthorizedError> {
+            let buffer = Buffer::<4> {
+                data: vec![1, 2, 3, 4],
+                checksum: 42,
+            };
+
+            let res: AuthorizedResult<Buffer<4>> = Authorizor::authorize(&buffer, &"user")?;
+            assert_eq!(res.inner.data, buffer.data);
+            assert_eq!(res.inner.checksum, 0);
+            assert_eq!(res.unauthorized_fields, vec!["checksum".to_string()]);
+
+            assert_eq!(Authorizor::authorize(&buffer, &"admin")?.inner.checksum, 42);
+
+            Ok(())
+        }
+    }
+
+    mod many_strict {
+        use std::cell::Cell;
+
+        use crate::prelude::*;
+        use crate::UnAuthorizedFields;
+
+        /// Counts its authorizations, fails when `owner` is empty.
+        #[derive(Debug)]
+        struct Document<'a> {
+            owner: &'static str,
+            authorizations: &'a Cell<usize>,
+        }
+
+        impl Authorized for Document<'_> {}
+
+        impl Authorizable for Document<'_> {
+            type Authorized = &'static str;
+
+            fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+                input: &Self,
+                _unauthorized_fields: &[S],
+            ) -> Result<Self::Authorized, AuthorizedError> {
+                Ok(input.owner)
+            }
+
+            fn filter_unauthorized_fields(_input: &Self, _scope: &Scope) -> UnAuthorizedFields {
+                vec![]
+            }
+
+            fn authorize(
+                input: &Self,
+                input_scope: &Scope,
+            ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+                input.authorizations.set(input.authorizations.get() + 1);
+
+                if input.owner.is_empty() {
+                    return Err(AuthorizedError::DisallowedScope("owner".into()));
+                }
+
+                Ok(AuthorizedResult {
+                    input_scope: input_scope.clone(),
+                    inner: input.owner,
+                    status: if input_scope.contains_token(input.owner) {
+                        AuthorizationStatus::Authorized
+                    } else {
+                        AuthorizationStatus::UnAuthorized
+                    },
+                    unauthorized_fields: vec![],
+                })
+            }
+        }
+
+        fn documents<'a>(owners: &[&'static str], count: &'a Cell<usize>) -> Vec<Document<'a>> {
+            owners
+                .iter()
+                .map(|&owner| Document {
+                    owner,
+                    authorizations: count,
+                })
+                .collect()
+        }
+
+        #[test]
+        fn every_item_must_be_authorized() -> Result<(), AuthorizedError> {
+            let count = Cell::new(0);
+
+            let results = Authorizor::authorize_many_strict(
+                &documents(&["alice", "bob"], &count),
+                &"alice bob",
+            )?;
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[1].inner, "bob");
+            assert!(Authorizor::authorize_many_strict(&[] as &[Document], &"alice")?.is_empty());
+
+            Ok(())
+        }
+
+        #[test]
+        fn first_failure_stops_the_authorization() {
+            let count = Cell::new(0);
+            let items = documents(&["alice", "bob", "carol", ""], &count);
+
+            match Authorizor::authorize_many_strict(&items, &"alice carol") {
+                Err(AuthorizedError::ItemUnauthorized { index, inner }) => {
+                    assert_eq!(index, 1);
+                    assert!(matches!(*inner, AuthorizedError::UnAuthorized(_)));
+                }
+                result => panic!("Unexpected result: {:?}", result),
+            }
+            assert_eq!(count.get(), 2);
+
+            count.set(0);
+            let result = Authorizor::authorize_many_strict(&items, &"alice bob carol");
+            assert!(matches!(result, Err(AuthorizedError::DisallowedScope(_))));
+            assert_eq!(count.get(), 4);
+
+            let error = Authorizor::authorize_many_strict(&items[1..], &"alice").unwrap_err();
+            assert!(error
+                .to_string()
+                .starts_with("Item 0: Scope `alice` is not authorized"));
+        }
+    }
+
+    mod chain {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use crate::prelude::*;
+
+        #[derive(Debug, Authorized)]
+        #[authorized(scope = "service")]
+        struct Account {
+            id: i32,
+            #[authorized(scope = "read:account")]
+            name: String,
+            #[authorized(scope = "read:email")]
+            email: String,
+            #[authorized(scope = "admin")]
+            balance: u32,
+        }
+
+        impl Authorized for Account {}
+
+        fn account() -> Account {
+            Account {
+                id: 1,
+                name: "name".into(),
+                email: "email".into(),
+                balance: 100,
+            }
+        }
+
+        fn scopes(inputs: &[&str]) -> Vec<Scope> {
+            inputs.iter().map(|input| input.parse().unwrap()).collect()
+        }
+
+        #[test]
+        fn redactions_add_up_along_the_chain() -> Result<(), AuthorizedError> {
+            let chain = scopes(&[
+                "service read:account read:email admin",
+                "service read:account read:email",
+                "service read:account",
+            ]);
+
+            let result = Authorizor::authorize_chain(&account(), &chain)?;
+            assert_eq!(result.status, AuthorizationStatus::Authorized);
+            assert_eq!(result.unauthorized_fields, vec!["balance", "email"]);
+            assert_eq!(result.input_scope, chain[2]);
+            assert_eq!(result.inner.id, 1);
+            assert_eq!(result.inner.name, "name");
+            assert!(result.inner.email.is_empty());
+            assert_eq!(result.inner.balance, 0);
+
+            // Fields redacted by an early step stay redacted.
+            let chain = scopes(&["service read:email", "service read:account"]);
+            let result = Authorizor::authorize_chain(&account(), &chain)?;
+            assert_eq!(result.unauthorized_fields, vec!["name", "balance", "email"]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn an_unauthorized_step_makes_the_chain_unauthorized() -> Result<(), AuthorizedError> {
+            let chain = scopes(&["read:account", "service read:account"]);
+            let result = Authorizor::authorize_chain(&account(), &chain)?;
+            assert_eq!(result.status, AuthorizationStatus::UnAuthorized);
+
+            let result = Authorizor::authorize_chain(&account(), &[])?;
+            assert_eq!(result.status, AuthorizationStatus::UnAuthorized);
+            assert_eq!(result.unauthorized_fields.len(), 3);
+
+            Ok(())
+        }
+
+        #[test]
+        fn collections_and_references_are_chained_item_by_item() -> Result<(), AuthorizedError> {
+            let chain = scopes(&["service read:account read:email", "service read:account"]);
+
+            let result = Authorizor::authorize_chain(&&account(), &chain)?;
+            assert_eq!(result.status, AuthorizationStatus::Authorized);
+            assert_eq!(result.unauthorized_fields, vec!["balance", "email"]);
+
+            let result = Authorizor::authorize_chain(&vec![account(), account()], &chain)?;
+            assert_eq!(result.inner.len(), 2);
+            assert_eq!(result.input_scope, chain[1]);
+            assert_eq!(result.inner[1].inner.name, "name");
+            assert!(result.inner[1].inner.email.is_empty());
+            assert_eq!(
+                result.inner[1].unauthorized_fields,
+                vec!["balance", "email"]
+            );
+
+            let result = Authorizor::authorize_chain(&[account()], &scopes(&["read:account"]))?;
+            assert_eq!(result.inner[0].status, AuthorizationStatus::UnAuthorized);
+            assert_eq!(result.inner[0].inner.name, "name");
+
+            Ok(())
+        }
+
+        /// Hand-written, without `scope_requirements`: only `authorize` knows its status.
+        struct Locked(u32);
+
+        impl Authorized for Locked {}
+
+        impl Authorizable for Locked {
+            type Authorized = u32;
+
+            fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(
+                input: &Self,
+                unauthorized_fields: &[S],
+            ) -> Result<Self::Authorized, AuthorizedError> {
+                Ok(if unauthorized_fields.is_empty() {
+                    input.0
+                } else {
+                    0
+                })
+            }
+
+            fn filter_unauthorized_fields(_input: &Self, scope: &Scope) -> UnAuthorizedFields {
+                if scope.allowed_count() == 0 {
+                    vec!["0".to_string()]
+                } else {
+                    vec![]
+                }
+            }
+
+            fn authorize(
+                input: &Self,
+                authorizer: &Scope,
+            ) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+                let unauthorized_fields = Self::filter_unauthorized_fields(input, authorizer);
+
+                Ok(AuthorizedResult {
+                    inner: Self::builder_authorized_struct(input, &unauthorized_fields)?,
+                    input_scope: authorizer.clone(),
+                    status: if "key".parse::<Scope>()?.allow_access(authorizer) {
+                        AuthorizationStatus::Authorized
+                    } else {
+                        AuthorizationStatus::UnAuthorized
+                    },
+                    unauthorized_fields,
+                })
+            }
+        }
+
+        #[test]
+        fn hand_written_impls_keep_their_status() -> Result<(), AuthorizedError> {
+            let result = Authorizor::authorize_chain(&Locked(7), &scopes(&["key", "key"]))?;
+            assert_eq!(result.status, AuthorizationStatus::Authorized);
+            assert_eq!(result.inner, 7);
+
+            let result = Authorizor::authorize_chain(&Locked(7), &scopes(&["key", "user"]))?;
+            assert_eq!(result.status, AuthorizationStatus::UnAuthorized);
+
+            let result = Authorizor::authorize_chain(&Locked(7), &scopes(&["key", ""]))?;
+            assert_eq!(result.unauthorized_fields, vec!["0"]);
+            assert_eq!(result.inner, 0);
+
+            Ok(())
+        }
+
+        static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+        /// Counts how many times it is built by its `Clone` impl.
+        #[derive(Debug, Authorized)]
+        struct Counted {
+            #[authorized(scope = "admin")]
+            secret: String,
+        }
+
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                CLONES.fetch_add(1, Ordering::SeqCst);
+                Self {
+                    secret: self.secret.clone(),
+                }
+            }
+        }
+
+        impl Authorized for Counted {}
+
+        #[test]
+        fn the_structure_is_built_once() -> Result<(), AuthorizedError> {
+            let counted = Counted {
+                secret: "secret".into(),
+            };
+
+            let chain = scopes(&["admin", "admin", "user"]);
+            let result = Authorizor::authorize_chain(&counted, &chain)?;
+            assert!(result.inner.secret.is_empty());
+            assert_eq!(CLONES.load(Ordering::SeqCst), 1);
+
+            Ok(())
+        }
+    }
+
+    mod hygiene {
+        use crate::prelude::*;
+
+        /// Fields named like the locals of the generated code.
+        #[derive(Debug, Authorized)]
+        struct Report {
+            #[authorized(scope = "admin")]
+            unauthorized_fields: Vec<String>,
+            arg_id: i32,
+            id: i32,
+            input: String,
+        }
+
+        impl Authorized for Report {}
+
+        #[derive(Clone, Debug, Authorized)]
+        struct ClonedReport {
+            #[authorized(scope = "admin")]
+            unauthorized_fields: Vec<String>,
+            authorized: bool,
+        }
+
+        impl Authorized for ClonedReport {}
+
+        #[test]
+        fn fields_can_be_named_like_generated_locals() -> Result<(), AuthorizedError> {
+            let report = Report {
+                unauthorized_fields: vec!["email".into()],
+                arg_id: 1,
+                id: 2,
+                input: "input".into(),
+            };
+
+            let result = Authorizor::authorize(&report, &"user")?;
+            assert_eq!(result.unauthorized_fields, vec!["unauthorized_fields"]);
+            assert!(result.inner.unauthorized_fields.is_empty());
+            assert_eq!(result.inner.arg_id, 1);
+            assert_eq!(result.inner.id, 2);
+            assert_eq!(result.inner.input, "input");
+
+            let report = ClonedReport {
+                unauthorized_fields: vec!["email".into()],
+                authorized: true,
+            };
+
+            let result = Authorizor::authorize(&report, &"user")?;
+            assert!(result.inner.unauthorized_fields.is_empty());
+            assert!(result.inner.authorized);
+
+            Ok(())
+        }
+    }
+
+    mod expiry {
+        use crate::prelude::*;
+
+        #[derive(Debug, Authorized)]
+        struct Offer {
+            name: String,
+            #[authorized(expiry = "2000-01-01")]
+            launch_price: u32,
+            #[authorized(scope = "member", expiry = "9999-12-31")]
+            member_price: u32,
+        }
+
+        impl Authorized for Offer {}
+
+        #[test]
+        fn expired_fields_are_redacted() -> Result<(), AuthorizedError> {
+            let offer = Offer {
+                name: "name".into(),
+                launch_price: 5,
+                member_price: 8,
+            };
+
+            let res = Authorizor::authorize(&offer, &"member")?;
+            assert_eq!(res.inner.launch_price, 0);
+            assert_eq!(res.inner.member_price, 8);
+            assert_eq!(res.unauthorized_fields, vec!["launch_price".to_string()]);
+
+            let res = Authorizor::authorize(&offer, &"guest")?;
+            assert_eq!(res.inner.name, "name");
+            assert_eq!(res.inner.member_price, 0);
+
+            let scope = "member".parse::<Scope>().unwrap();
+            assert!(!Offer::is_authorized_for_field(
+                &offer,
+                "launch_price",
+                &scope
+            ));
+            assert!(Offer::is_authorized_for_field(
+                &offer,
+                "member_price",
+                &scope
+            ));
+            assert!(Offer::is_authorized_for_field(&offer, "name", &scope));
+
+            let names: Vec<&str> = Offer::field_expiries().iter().map(|(n, _)| *n).collect();
+            assert_eq!(names, vec!["launch_price", "member_price"]);
+
+            Ok(())
+        }
+    }
+
+    mod sensitive {
+        use crate::prelude::*;
+        use crate::SENSITIVE_FIELD;
+
+        #[derive(Debug, Clone, Authorized)]
+        struct Citizen {
+            name: String,
+            #[authorized(scope = "admin", sensitive)]
+            social_security_number: String,
+            #[authorized(scope = "tax", sensitive)]
+            income: u32,
+            #[authorized(scope = "contact")]
+            email: String,
+        }
+
+        impl Authorized for Citizen {}
+
+        #[test]
+        fn sensitive_field_names_are_hidden() -> Result<(), AuthorizedError> {
+            let citizen = Citizen {
+                name: "name".into(),
+                social_security_number: "ssn".into(),
+                income: 42,
+                email: "email".into(),
+            };
+
+            let res = Authorizor::authorize(&citizen, &"user")?;
+            assert_eq!(
+                res.unauthorized_fields,
+                vec![SENSITIVE_FIELD.to_string(), "email".to_string()]
+            );
+            assert!(res.inner.social_security_number.is_empty());
+            assert_eq!(res.inner.income, 0);
+
+            let res = Authorizor::authorize(&citizen, &"admin tax contact")?;
+            assert!(res.unauthorized_fields.is_empty());
+            assert_eq!(res.inner.social_security_number, "ssn");
+            assert_eq!(res.inner.income, 42);
+
+            let error = Authorizor::authorize_all_fields(&citizen, &"contact").unwrap_err();
+            assert!(!error.to_string().contains("social_security_number"));
+            assert!(!error.to_string().contains("income"));
+
+            Ok(())
+        }
+
+        #[test]
+        fn sensitive_fields_are_authorized_on_their_own() -> Result<(), AuthorizedError> {
+            let citizen = Citizen {
+                name: "name".into(),
+                social_security_number: "ssn".into(),
+                income: 42,
+                email: "email".into(),
+            };
+
+            let res = Authorizor::authorize(&citizen, &"tax contact")?;
+            assert_eq!(res.unauthorized_fields, vec![SENSITIVE_FIELD.to_string()]);
+            assert!(res.inner.social_security_number.is_empty());
+            assert_eq!(res.inner.income, 42);
+
+            assert_eq!(
+                Authorizor::unauthorized_fields_for(&citizen, &"tax contact")?,
+                vec![SENSITIVE_FIELD.to_string()]
+            );
+
+            // The required scope is still given for the masked fields.
+            match Authorizor::authorize_all_fields(&citizen, &"tax contact") {
+                Err(AuthorizedError::PartiallyUnauthorized {
+                    unauthorized_fields,
+                    scope,
+                    ..
+                }) => {
+                    assert_eq!(unauthorized_fields, vec![SENSITIVE_FIELD.to_string()]);
+                    assert_eq!(*scope, "admin".parse::<Scope>()?);
+                }
+                result => panic!("Unexpected result: {:?}", result),
+            }
+
+            let mut field_scopes = std::collections::HashMap::new();
+            field_scopes.insert("social_security_number", "tax".parse::<Scope>()?);
+            field_scopes.insert("income", "tax".parse::<Scope>()?);
+            let res = Authorizor::authorize_group(&citizen, &field_scopes)?;
+            assert_eq!(res.unauthorized_fields, vec![SENSITIVE_FIELD.to_string()]);
+            assert!(res.inner.social_security_number.is_empty());
+            assert_eq!(res.inner.income, 42);
+
+            let res = Authorizor::authorize(&citizen, &"admin tax contact")?
+                .filter_fields(|field| field != "income");
+            assert_eq!(res.unauthorized_fields, vec![SENSITIVE_FIELD.to_string()]);
+
+            Ok(())
+        }
+    }
+
+    mod group {
+        use std::collections::HashMap;
+
+        use crate::prelude::*;
+
+        #[derive(Debug, Clone, PartialEq, Authorized)]
+        struct Profile {
+            name: String,
+            #[authorized(scope = "contact")]
+            email: String,
+            #[authorized(scope = "billing")]
+            iban: String,
+            #[authorized(scope = "!guest")]
+            city: String,
+        }
+
+        impl Authorized for Profile {}
+
+        #[test]
+        fn fields_are_checked_against_their_own_scope() -> Result<(), AuthorizedError> {
+            let profile = Profile {
+                name: "name".into(),
+                email: "email".into(),
+                iban: "iban".into(),
+                city: "city".into(),
+            };
+
+            let mut field_scopes = HashMap::new();
+            field_scopes.insert("email", "contact".parse::<Scope>()?);
+            field_scopes.insert("city", "guest".parse::<Scope>()?);
+
+            field_scopes.insert("name", "guest".parse::<Scope>()?);
+            field_scopes.insert("iban", "contact".parse::<Scope>()?);
+
+            let res = Authorizor::authorize_group(&profile, &field_scopes)?;
+            assert_eq!(
+                res.unauthorized_fields,
+                vec!["iban".to_string(), "city".to_string()]
+            );
+            assert_eq!(res.inner.name, "name");
+            assert_eq!(res.inner.email, "email");
+            assert!(res.inner.iban.is_empty());
+            assert!(res.inner.city.is_empty());
+
+            // Unlisted fields are authorized.
+            let res = Authorizor::authorize_group(&profile, &HashMap::new())?;
+            assert!(res.unauthorized_fields.is_empty());
+            assert_eq!(res.inner, profile);
+
+            Ok(())
+        }
+
+        #[test]
+        fn collections_and_references_are_grouped_item_by_item() -> Result<(), AuthorizedError> {
+            let profile = Profile {
+                name: "name".into(),
+                email: "email".into(),
+                iban: "iban".into(),
+                city: "city".into(),
+            };
+
+            let mut field_scopes = HashMap::new();
+            field_scopes.insert("email", "guest".parse::<Scope>()?);
+
+            let res = Authorizor::authorize_group(&&profile, &field_scopes)?;
+            assert_eq!(res.unauthorized_fields, vec!["email".to_string()]);
+            assert!(res.inner.email.is_empty());
+
+            let profiles = vec![profile.clone(), profile.clone()];
+            let res = Authorizor::authorize_group(&profiles, &field_scopes)?;
+            assert_eq!(res.inner.len(), 2);
+            assert_eq!(res.inner[1].inner.name, "name");
+            assert!(res.inner[1].inner.email.is_empty());
+            assert_eq!(res.inner[1].unauthorized_fields, vec!["email".to_string()]);
+            assert_eq!(
+                Profile::filter_unauthorized_fields_with_map(&profile, &field_scopes),
+                vec!["email".to_string()]
+            );
+            assert_eq!(
+                <Vec<Profile>>::filter_unauthorized_fields_with_map(&profiles, &field_scopes),
+                vec!["0.email".to_string(), "1.email".to_string()]
+            );
+
+            let res = Authorizor::authorize_group(&[profile], &field_scopes)?;
+            assert_eq!(res.inner[0].inner.iban, "iban");
+            assert!(res.inner[0].inner.email.is_empty());
+
+            Ok(())
+        }
+
+        #[test]
+        fn scope_requirements_list_the_scoped_fields() -> Result<(), AuthorizedError> {
+            let requirements = Authorizor::build_scope_for::<Profile>();
+            assert_eq!(requirements.global_scope, None);
+            assert_eq!(requirements.field_scopes.len(), 3);
+            assert_eq!(requirements.field_scopes["email"], "contact".parse()?);
+            assert_eq!(requirements.field_scopes["city"], "!guest".parse()?);
+
+            let requirements = Authorizor::build_scope_for::<&Account>();
+            assert_eq!(requirements.global_scope, Some("account".parse()?));
+            assert_eq!(requirements.field_scopes["iban"], "billing".parse()?);
+
+            assert_eq!(
+                Authorizor::build_scope_for::<Vec<Profile>>(),
+                ScopeRequirements::default()
+            );
+
+            Ok(())
+        }
+
+        #[derive(Debug, Clone, Authorized)]
+        #[authorized(groups(
+            personal = "read:personal",
+            financial = "read:personal read:financial"
+        ))]
+        struct Employee {
+            id: i32,
+            #[authorized(group = "personal")]
+            address: String,
+            #[authorized(group = "financial")]
+            salary: u32,
+            #[authorized(scope = "hr", group = "financial")]
+            bonus: u32,
+        }
+
+        impl Authorized for Employee {}
+
+        #[test]
+        fn grouped_fields_require_the_group_scope() -> Result<(), AuthorizedError> {
+            let employee = Employee {
+                id: 1,
+                address: "address".into(),
+                salary: 1000,
+                bonus: 100,
+            };
+            let unauthorized = |scope: &str| Authorizor::unauthorized_fields_for(&employee, &scope);
+
+            assert_eq!(unauthorized("")?, vec!["address", "salary", "bonus"]);
+            assert_eq!(unauthorized("read:personal")?, vec!["salary", "bonus"]);
+            assert_eq!(
+                unauthorized("read:financial")?,
+                vec!["address", "salary", "bonus"]
+            );
+            assert_eq!(unauthorized("read:personal read:financial")?, vec!["bonus"]);
+            assert!(unauthorized("hr read:personal read:financial")?.is_empty());
+
+            assert_eq!(Employee::field_scope("address"), Some("read:personal"));
+            assert_eq!(
+                Employee::field_scope("bonus"),
+                Some("hr read:personal read:financial")
+            );
+
+            let res = Authorizor::authorize(&employee, &"read:personal")?;
+            assert_eq!(res.inner.address, "address");
+            assert_eq!(res.inner.salary, 0);
+
+            Ok(())
+        }
+
+        #[test]
+        fn unauthorized_fields_are_listed_without_authorizing() -> Result<(), AuthorizedError> {
+            let profile = Profile {
+                name: "name".into(),
+                email: "email".into(),
+                iban: "iban".into(),
+                city: "city".into(),
+            };
+
+            for scope in &["", "contact guest", "billing"] {
+                assert_eq!(
+                    Authorizor::unauthorized_fields_for(&profile, scope)?,
+                    Authorizor::authorize(&profile, scope)?.unauthorized_fields
+                );
+            }
+            assert!(Authorizor::unauthorized_fields_for(&profile, &"read:\"user\"").is_err());
+
+            Ok(())
+        }
+
+        #[test]
+        fn missing_scopes_fall_back_to_a_default() -> Result<(), AuthorizedError> {
+            let profile = Profile {
+                name: "name".into(),
+                email: "email".into(),
+                iban: "iban".into(),
+                city: "city".into(),
+            };
+
+            assert_eq!(
+                Authorizor::authorize_with_default(&profile, None, "contact")?,
+                Authorizor::authorize(&profile, &"contact")?
+            );
+            assert_eq!(
+                Authorizor::authorize_with_default(&profile, Some("billing"), "contact")?,
+                Authorizor::authorize(&profile, &"billing")?
+            );
+            assert_eq!(
+                Authorizor::authorize_with_empty_scope_fallback(&profile, None::<&str>)?,
+                Authorizor::authorize(&profile, &"")?
+            );
+            assert_eq!(
+                Authorizor::authorize_with_empty_scope_fallback(&profile, Some("guest"))?,
+                Authorizor::authorize(&profile, &"guest")?
+            );
+
+            Ok(())
+        }
+
+        #[test]
+        fn single_fields_agree_with_the_filtered_fields() -> Result<(), AuthorizedError> {
+            let profile = Profile {
+                name: "name".into(),
+                email: "email".into(),
+                iban: "iban".into(),
+                city: "city".into(),
+            };
+
+            for scope in &["", "contact", "billing guest", "contact billing !guest"] {
+                let scope = scope.parse::<Scope>()?;
+                let unauthorized = Profile::filter_unauthorized_fields(&profile, &scope);
+
+                for field in Profile::field_names() {
+                    assert_eq!(
+                        Profile::is_authorized_for_field(&profile, field, &scope),
+                        !unauthorized.iter().any(|f| f == field),
+                    );
+                    assert_eq!(
+                        <&Profile>::is_authorized_for_field(&&profile, field, &scope),
+                        Profile::is_authorized_for_field(&profile, field, &scope),
+                    );
+                }
+            }
+            assert!(Profile::is_authorized_for_field(
+                &profile,
+                "unknown",
+                &Scope::from_tokens(&[], &[])
+            ));
+
+            Ok(())
+        }
+
+        #[test]
+        fn fields_can_be_listed_with_their_scope() {
+            assert_eq!(Profile::field_names(), &["name", "email", "iban", "city"]);
+            assert_eq!(
+                Profile::scoped_field_names(),
+                &[
+                    ("name", None),
+                    ("email", Some("contact")),
+                    ("iban", Some("billing")),
+                    ("city", Some("!guest")),
+                ]
+            );
+            assert_eq!(
+                <&Profile>::scoped_field_names(),
+                Profile::scoped_field_names()
+            );
+        }
+
+        #[test]
+        fn strict_authorization_rejects_redacted_fields() -> Result<(), AuthorizedError> {
+            let profile = Profile {
+                name: "name".into(),
+                email: "email".into(),
+                iban: "iban".into(),
+                city: "city".into(),
+            };
+
+            let inner = Authorizor::authorize_strict(&profile, &"contact billing")?;
+            assert_eq!(inner.iban, "iban");
+
+            match Authorizor::authorize_strict(&profile, &"contact guest") {
+                Err(AuthorizedError::InsufficientScope {
+                    required_fields,
+                    provided_scope,
+                }) => {
+                    assert_eq!(
+                        required_fields,
+                        vec!["iban".to_string(), "city".to_string()]
+                    );
+                    assert_eq!(*provided_scope, "contact guest".parse::<Scope>()?);
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            Ok(())
+        }
+
+        #[derive(Debug, Clone, Authorized)]
+        #[authorized(scope = "account")]
+        struct Account {
+            id: i32,
+            #[authorized(scope = "billing")]
+            iban: String,
+        }
+
+        impl Authorized for Account {}
+
+        fn account_iban(account: &Account, scope: &str) -> Result<String, AuthorizedError> {
+            Ok(Authorizor::authorize_or_unauthorized(account, &scope)?
+                .inner
+                .iban)
+        }
+
+        #[test]
+        fn unauthorized_structures_are_errors() -> Result<(), AuthorizedError> {
+            let account = Account {
+                id: 1,
+                iban: "iban".into(),
+            };
+
+            assert_eq!(account_iban(&account, "account billing")?, "iban");
+            assert_eq!(account_iban(&account, "account")?, "");
+
+            match account_iban(&account, "billing") {
+                Err(AuthorizedError::UnAuthorized(error)) => {
+                    assert_eq!(error.input_scope, "billing".parse::<Scope>()?);
+                    assert_eq!(error.struct_type, "authorized::tests::group::Account");
+                    assert_eq!(
+                        error.to_string(),
+                        "Scope `billing` is not authorized to access authorized::tests::group::Account"
+                    );
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        fn partially_unauthorized_errors_give_the_required_scope() -> Result<(), AuthorizedError> {
+            let profile = Profile {
+                name: "name".into(),
+                email: "email".into(),
+                iban: "iban".into(),
+                city: "city".into(),
+            };
+
+            let res = Authorizor::authorize_all_fields(&profile, &"contact billing")?;
+            assert_eq!(res.inner.email, "email");
+
+            let error = Authorizor::authorize_all_fields(&profile, &"guest").unwrap_err();
+            match &error {
+                AuthorizedError::PartiallyUnauthorized {
+                    struct_type,
+                    unauthorized_fields,
+                    scope,
+                } => {
+                    assert!(struct_type.ends_with("Profile"));
+                    assert_eq!(unauthorized_fields.len(), 3);
+                    assert_eq!(**scope, "billing contact !guest".parse::<Scope>()?);
+                }
+                other => panic!("unexpected error: {:?}", other),
+            }
+            assert_eq!(
+                error.to_string(),
+                "Fields email, iban, city of authorized::tests::group::Profile are unauthorized, \
+                 a scope matching `billing contact !guest` is required to access them"
+            );
+
+            Ok(())
+        }
+    }
+
+    mod route {
+        use super::block_on;
+        use crate::prelude::*;
+
+        #[authorize_route(scope = "admin")]
+        fn delete_user(scope: &Scope, id: i32) -> Result<i32, AuthorizedError> {
+            Ok(id)
+        }
+
+        #[authorize_route(scope = "admin !guest")]
+        async fn ban_user(id: i32, scope: Scope) -> Result<i32, AuthorizedError> {
+            Ok(id)
+        }
+
+        #[test]
+        fn sync_routes_check_the_scope() {
+            let admin = "admin".parse::<Scope>().unwrap();
+            assert_eq!(delete_user(&admin, 1).unwrap(), 1);
+
+            let user = "user".parse::<Scope>().unwrap();
+            match delete_user(&user, 1) {
+                Err(AuthorizedError::InsufficientScope {
+                    required_fields,
+                    provided_scope,
+                }) => {
+                    assert!(required_fields.is_empty());
+                    assert_eq!(*provided_scope, user);
+                }
+                result => panic!("Unexpected result: {:?}", result),
+            }
+        }
+
+        #[test]
+        fn async_routes_check_the_scope() {
+            let admin = "admin user".parse::<Scope>().unwrap();
+            assert_eq!(block_on(ban_user(2, admin)).unwrap(), 2);
+
+            let guest = "admin guest".parse::<Scope>().unwrap();
+            assert!(matches!(
+                block_on(ban_user(2, guest)),
+                Err(AuthorizedError::InsufficientScope { .. })
+            ));
+        }
+    }
+
+    #[cfg(feature = "with_async")]
+    mod future {
+        use std::future::{ready, Future};
+
+        use super::{block_on, MyUser};
+        use crate::prelude::*;
+
+        #[test]
+        fn authorized_futures_keep_their_metadata() {
+            let result = AuthorizedResult {
+                input_scope: "guest".parse::<Scope>().unwrap(),
+                inner: ready(42),
+                status: AuthorizationStatus::UnAuthorized,
+                unauthorized_fields: vec!["email".into()],
+            };
+
+            assert_eq!(
+                block_on(result),
+                AuthorizedResult {
+                    input_scope: "guest".parse::<Scope>().unwrap(),
+                    inner: 42,
+                    status: AuthorizationStatus::UnAuthorized,
+                    unauthorized_fields: vec!["email".into()],
+                }
+            );
+
+            let pinned: AuthorizedResult<std::pin::Pin<Box<dyn Future<Output = i32>>>> =
+                AuthorizedResult {
+                    input_scope: "admin".parse::<Scope>().unwrap(),
+                    inner: Box::pin(async { 7 }),
+                    status: AuthorizationStatus::Authorized,
+                    unauthorized_fields: vec![],
+                };
+            assert_eq!(block_on(pinned).inner, 7);
+        }
+
+        #[test]
+        fn futures_are_authorized_once_resolved() -> Result<(), AuthorizedError> {
+            let user = MyUser {
+                name: "name".into(),
+                pass: "pass".into(),
+                email: "email".into(),
+            };
+
+            let future = Authorizor::authorize_future(ready(user.clone()), &"read:user")?;
+            assert_eq!(
+                block_on(future)?,
+                Authorizor::authorize(&user, &"read:user")?
+            );
+
+            assert!(Authorizor::authorize_future(ready(user), &"read:\"user\"").is_err());
+
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "with_validator")]
+    mod validation {
+        use validator::Validate;
+
+        use crate::prelude::*;
+
+        #[derive(Debug, Authorized, Validate)]
+        struct Member {
+            #[validate(length(min = 1))]
+            name: String,
+            #[authorized(scope = "admin")]
+            #[validate(length(min = 1))]
+            email: String,
+            #[authorized(scope = "billing", sensitive)]
+            #[validate(range(min = 1))]
+            card: u32,
+        }
+
+        impl Authorized for Member {}
+
+        #[test]
+        fn unauthorized_fields_are_not_validated() {
+            let member = Member {
+                name: "name".into(),
+                email: String::new(),
+                card: 0,
+            };
+            let scope = |scope: &str| scope.parse::<Scope>().unwrap();
+
+            assert!(member.validate().is_err());
+            assert!(member.validate_authorized(&scope("user")).is_ok());
+
+            let errors = member.validate_authorized(&scope("admin")).unwrap_err();
+            assert_eq!(
+                errors.errors().keys().copied().collect::<Vec<_>>(),
+                vec!["email"]
+            );
+
+            let errors = member.validate_authorized(&scope("billing")).unwrap_err();
+            assert!(errors.errors().contains_key("card"));
+
+            let member = Member {
+                name: String::new(),
+                ..member
+            };
+            let errors = member.validate_authorized(&scope("user")).unwrap_err();
+            assert!(errors.errors().contains_key("name"));
+        }
+    }
+
+    #[cfg(feature = "with_tracing")]
+    mod tracing {
+        use crate::prelude::*;
+
+        #[derive(Debug, Authorized)]
+        #[authorized(scope = "admin")]
+        struct Secret {
+            #[authorized(scope = "owner")]
+            value: String,
+        }
+
+        impl Authorized for Secret {}
+
+        #[tracing_test::traced_test]
+        #[test]
+        fn authorizations_are_logged() {
+            let secret = Secret {
+                value: "value".into(),
+            };
+
+            assert!(Authorizor::authorize_and_log(&secret, &"admin owner").is_ok());
+            assert!(logs_contain("INFO"));
+            assert!(logs_contain("status=Authorized unauthorized_fields=0"));
+
+            assert!(Authorizor::authorize_and_log(&secret, &"guest").is_ok());
+            assert!(logs_contain("WARN"));
+            assert!(logs_contain("status=UnAuthorized unauthorized_fields=1"));
+
+            assert!(Authorizor::authorize_and_log(&secret, &"\"guest\"").is_err());
+            assert!(logs_contain("ERROR"));
+            assert!(logs_contain("authorization failed"));
+        }
+    }
 }
@@ -1,6 +1,15 @@
-pub use crate::error::AuthorizedError;
-pub use crate::result::{AuthorizationStatus, AuthorizedResult};
+pub use crate::audit::{FieldAuditDecision, FieldAuditEvent, FieldAuditTrail};
+#[cfg(feature = "with_cache")]
+pub use crate::cache::{CachedAuthorizor, CachedAuthorizorBuilder};
+pub use crate::error::{AuthorizedError, UnauthorizedResult};
+pub use crate::explain::{AuthorizationExplanation, FieldDecision};
+pub use crate::ext::AuthorizedExt;
+pub use crate::fallible::FieldBuildError;
+pub use crate::result::{
+    AuthorizationStatus, AuthorizedResult, LazyAuthorizedResult, UnauthorizedAccessError,
+};
 pub use crate::scope::Scope;
-pub use crate::UnAuthorizedFields;
+pub use crate::whitelist::ScopeWhitelist;
 pub use crate::{Authorizable, Authorized, Authorizor};
-pub use authorized_derive::Authorized;
+pub use crate::{ScopeRequirements, UnAuthorizedFields};
+pub use authorized_derive::{authorize_route, scope, Authorized};
@@ -1,7 +1,16 @@
+#[cfg(feature = "with_async")]
+use std::future::Future;
+#[cfg(feature = "with_async")]
+use std::pin::Pin;
+#[cfg(feature = "with_async")]
+use std::task::{Context, Poll};
+
+use crate::error::{AuthorizedError, UnauthorizedResult};
+use crate::Authorizable;
 use crate::Scope;
 use crate::UnAuthorizedFields;
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct AuthorizedResult<T> {
     pub input_scope: Scope,
     pub inner: T,
@@ -9,8 +18,519 @@ pub struct AuthorizedResult<T> {
     pub unauthorized_fields: UnAuthorizedFields,
 }
 
-#[derive(PartialEq, Debug)]
+impl<T> AuthorizedResult<T> {
+    /// Converts from `&AuthorizedResult<T>` to `AuthorizedResult<&T>`, cloning the
+    /// authorization metadata.
+    #[must_use]
+    pub fn as_ref(&self) -> AuthorizedResult<&T> {
+        AuthorizedResult {
+            input_scope: self.input_scope.clone(),
+            inner: &self.inner,
+            status: self.status,
+            unauthorized_fields: self.unauthorized_fields.clone(),
+        }
+    }
+
+    /// Converts from `&mut AuthorizedResult<T>` to `AuthorizedResult<&mut T>`, cloning the
+    /// authorization metadata.
+    #[must_use]
+    pub fn as_mut(&mut self) -> AuthorizedResult<&mut T> {
+        AuthorizedResult {
+            input_scope: self.input_scope.clone(),
+            inner: &mut self.inner,
+            status: self.status,
+            unauthorized_fields: self.unauthorized_fields.clone(),
+        }
+    }
+
+    /// Maps the borrowed inner value to a new `AuthorizedResult` with the same metadata,
+    /// without consuming `self`.
+    #[must_use]
+    pub fn map_ref<U, F: Fn(&T) -> U>(&self, f: F) -> AuthorizedResult<U> {
+        AuthorizedResult {
+            input_scope: self.input_scope.clone(),
+            inner: f(&self.inner),
+            status: self.status,
+            unauthorized_fields: self.unauthorized_fields.clone(),
+        }
+    }
+
+    /// Calls `f` with the result, e.g. to log it, and returns the result unchanged.
+    #[must_use]
+    pub fn inspect<F: FnOnce(&Self)>(self, f: F) -> Self {
+        f(&self);
+        self
+    }
+
+    /// Calls `f` with the unauthorized fields and the input scope if a field is unauthorized,
+    /// and returns the result unchanged.
+    #[must_use]
+    pub fn inspect_unauthorized<F: FnOnce(&UnAuthorizedFields, &Scope)>(self, f: F) -> Self {
+        if !self.unauthorized_fields.is_empty() {
+            f(&self.unauthorized_fields, &self.input_scope);
+        }
+
+        self
+    }
+
+    /// Returns the inner value if the structure is authorized and no field has been redacted,
+    /// computes it from the status and the unauthorized fields otherwise.
+    pub fn recover_unauthorized<F: FnOnce(AuthorizationStatus, &UnAuthorizedFields) -> T>(
+        self,
+        f: F,
+    ) -> T {
+        if self.status == AuthorizationStatus::Authorized && self.unauthorized_fields.is_empty() {
+            self.inner
+        } else {
+            f(self.status, &self.unauthorized_fields)
+        }
+    }
+
+    /// Returns the inner value if the structure is authorized and no field has been redacted,
+    /// `default` otherwise.
+    pub fn recover_unauthorized_or(self, default: T) -> T
+    where
+        T: Default,
+    {
+        self.recover_unauthorized(|_, _| default)
+    }
+
+    /// Keeps the result if the structure is authorized, so an unauthorized access can be
+    /// propagated with `?`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `UnauthorizedAccessError` if the status is `UnAuthorized`.
+    pub fn into_authorized(self) -> Result<Self, UnauthorizedAccessError> {
+        match self.status {
+            AuthorizationStatus::Authorized => Ok(self),
+            AuthorizationStatus::UnAuthorized => Err(UnauthorizedResult {
+                input_scope: self.input_scope,
+                struct_type: std::any::type_name::<T>(),
+            }),
+        }
+    }
+
+    /// Returns the inner value if the structure is authorized.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the input scope and the unauthorized fields if the status is `UnAuthorized`.
+    #[track_caller]
+    pub fn unwrap(self) -> T {
+        if self.status == AuthorizationStatus::Authorized {
+            return self.inner;
+        }
+
+        panic!(
+            "called `AuthorizedResult::unwrap()` on an `UnAuthorized` result, input scope: `{}`, unauthorized fields: {:?}",
+            self.input_scope, self.unauthorized_fields
+        )
+    }
+
+    /// Returns the inner value if the structure is authorized.
+    ///
+    /// # Panics
+    ///
+    /// Panics with `msg` if the status is `UnAuthorized`.
+    #[track_caller]
+    pub fn expect(self, msg: &str) -> T {
+        if self.status == AuthorizationStatus::Authorized {
+            return self.inner;
+        }
+
+        panic!("{}", msg)
+    }
+
+    /// Returns the inner value if the structure is authorized, `default` otherwise.
+    #[track_caller]
+    pub fn unwrap_or(self, default: T) -> T {
+        self.unwrap_or_else(|_| default)
+    }
+
+    /// Returns the inner value if the structure is authorized, computes it from the status
+    /// otherwise.
+    #[track_caller]
+    pub fn unwrap_or_else<F: FnOnce(AuthorizationStatus) -> T>(self, f: F) -> T {
+        match self.status {
+            AuthorizationStatus::Authorized => self.inner,
+            status @ AuthorizationStatus::UnAuthorized => f(status),
+        }
+    }
+}
+
+impl<T: Authorizable> AuthorizedResult<T> {
+    /// Records every field of `T` for which `predicate` returns `false` as unauthorized, e.g.
+    /// the fields which aren't part of a GraphQL selection set. `inner` and `status` are left
+    /// unchanged.
+    ///
+    /// The fields are listed by
+    /// [`Authorizable::field_names`](../trait.Authorizable.html#method.field_names). Sensitive
+    /// fields are recorded as [`SENSITIVE_FIELD`](../constant.SENSITIVE_FIELD.html).
+    #[must_use]
+    pub fn filter_fields<F: Fn(&str) -> bool>(mut self, predicate: F) -> Self {
+        for field in T::field_names() {
+            let name = if T::is_sensitive_field(field) {
+                crate::SENSITIVE_FIELD
+            } else {
+                field
+            };
+
+            if !predicate(field) && !self.unauthorized_fields.iter().any(|f| f == name) {
+                self.unauthorized_fields.push(name.to_string());
+            }
+        }
+
+        self
+    }
+}
+
+impl<T> AuthorizedResult<Vec<T>> {
+    /// Iterates over references to the authorized items.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.inner.iter()
+    }
+}
+
+impl<T> AuthorizedResult<Option<T>> {
+    /// Transposes an `AuthorizedResult` of an `Option` into an `Option` of an
+    /// `AuthorizedResult`, keeping the authorization metadata.
+    #[must_use]
+    pub fn transpose(self) -> Option<AuthorizedResult<T>> {
+        let Self {
+            input_scope,
+            inner,
+            status,
+            unauthorized_fields,
+        } = self;
+
+        inner.map(|inner| AuthorizedResult {
+            input_scope,
+            inner,
+            status,
+            unauthorized_fields,
+        })
+    }
+}
+
+impl<T, E> AuthorizedResult<Result<T, E>> {
+    /// Transposes an `AuthorizedResult` of a `Result` into a `Result` of an
+    /// `AuthorizedResult`, keeping the authorization metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns the inner error.
+    pub fn transpose(self) -> Result<AuthorizedResult<T>, E> {
+        let Self {
+            input_scope,
+            inner,
+            status,
+            unauthorized_fields,
+        } = self;
+
+        inner.map(|inner| AuthorizedResult {
+            input_scope,
+            inner,
+            status,
+            unauthorized_fields,
+        })
+    }
+}
+
+/// Iterates over the authorized items, e.g. the `AuthorizedResult<T>` items produced by
+/// authorizing a `Vec<T>`.
+impl<T> IntoIterator for AuthorizedResult<Vec<T>> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a AuthorizedResult<Vec<T>> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An authorized future resolves to an `AuthorizedResult` of its output, with the same
+/// metadata. The future must be `Unpin`, box it with `Box::pin` otherwise.
+#[cfg(feature = "with_async")]
+impl<T: Future + Unpin> Future for AuthorizedResult<T> {
+    type Output = AuthorizedResult<T::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.inner)
+            .poll(cx)
+            .map(|inner| AuthorizedResult {
+                input_scope: self.input_scope.clone(),
+                inner,
+                status: self.status,
+                unauthorized_fields: self.unauthorized_fields.clone(),
+            })
+    }
+}
+
+/// The error of [`AuthorizedResult::into_authorized`](struct.AuthorizedResult.html#method.into_authorized).
+///
+/// It is an alias of `UnauthorizedResult`, which already holds the input scope and the
+/// structure type and reports the same message, so both convert to an `AuthorizedError` the
+/// same way.
+pub type UnauthorizedAccessError = UnauthorizedResult;
+
+/// An authorization whose scope has been parsed but whose fields haven't been filtered yet,
+/// returned by [`Authorizor::authorize_lazy`](../struct.Authorizor.html#method.authorize_lazy).
+///
+/// The authorized value is only built by [`materialize`](#method.materialize), so callers which
+/// never need it don't pay for the filtering nor for the clone of the input.
+#[derive(Debug)]
+pub struct LazyAuthorizedResult<'a, A: Authorizable> {
+    inner: &'a A,
+    scope: Scope,
+}
+
+impl<'a, A: Authorizable> LazyAuthorizedResult<'a, A> {
+    pub(crate) const fn new(inner: &'a A, scope: Scope) -> Self {
+        Self { inner, scope }
+    }
+
+    #[must_use]
+    pub const fn scope(&self) -> &Scope {
+        &self.scope
+    }
+
+    /// Filter the unauthorized fields and build the authorized value, like
+    /// [`Authorizor::authorize`](../struct.Authorizor.html#method.authorize) does.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of [`Authorizable::authorize`](../trait.Authorizable.html#method.authorize).
+    pub fn materialize(&self) -> Result<AuthorizedResult<A::Authorized>, AuthorizedError> {
+        A::authorize(self.inner, &self.scope)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "with_serde",
+    derive(::serde::Serialize, ::serde::Deserialize),
+    serde(rename_all = "PascalCase")
+)]
 pub enum AuthorizationStatus {
     Authorized,
     UnAuthorized,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result() -> AuthorizedResult<String> {
+        AuthorizedResult {
+            input_scope: "read:user".parse::<Scope>().unwrap(),
+            inner: "name".into(),
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: vec!["email".into()],
+        }
+    }
+
+    #[test]
+    fn borrowed_results_keep_metadata() {
+        let mut res = result();
+
+        let borrowed = res.as_ref();
+        assert_eq!(borrowed.inner, "name");
+        assert_eq!(borrowed.input_scope, res.input_scope);
+        assert_eq!(borrowed.unauthorized_fields, res.unauthorized_fields);
+
+        let len = res.map_ref(String::len);
+        assert_eq!(len.inner, 4);
+        assert_eq!(len.status, AuthorizationStatus::Authorized);
+
+        res.as_mut().inner.push('2');
+        assert_eq!(res.inner, "name2");
+    }
+
+    #[test]
+    fn vec_results_can_be_iterated() {
+        let users = AuthorizedResult {
+            input_scope: "read:user".parse::<Scope>().unwrap(),
+            inner: vec![result(), result()],
+            status: AuthorizationStatus::Authorized,
+            unauthorized_fields: vec![],
+        };
+
+        let mut count = 0;
+        for user in &users {
+            assert_eq!(user.inner, "name");
+            count += 1;
+        }
+        assert_eq!(count, 2);
+
+        let names: Vec<String> = users.into_iter().map(|user| user.inner).collect();
+        assert_eq!(names, vec!["name".to_string(), "name".to_string()]);
+    }
+
+    fn authorized_name(result: AuthorizedResult<String>) -> Result<String, UnauthorizedResult> {
+        let authorized = result.into_authorized()?;
+
+        Ok(authorized.inner)
+    }
+
+    #[test]
+    fn unauthorized_results_are_errors() {
+        assert_eq!(authorized_name(result()), Ok("name".to_string()));
+
+        let error = authorized_name(AuthorizedResult {
+            status: AuthorizationStatus::UnAuthorized,
+            ..result()
+        })
+        .unwrap_err();
+        assert_eq!(error.input_scope, "read:user".parse::<Scope>().unwrap());
+        assert_eq!(
+            error.to_string(),
+            "Scope `read:user` is not authorized to access alloc::string::String"
+        );
+    }
+
+    #[test]
+    fn results_can_be_inspected() {
+        let mut calls = 0;
+        let res = result().inspect(|res| {
+            assert_eq!(res.inner, "name");
+            calls += 1;
+        });
+        assert_eq!(calls, 1);
+        assert_eq!(res, result());
+
+        let mut unauthorized = vec![];
+        let res = res.inspect_unauthorized(|fields, scope| {
+            unauthorized.push((fields.clone(), scope.clone()));
+        });
+        assert_eq!(res, result());
+        assert_eq!(
+            unauthorized,
+            vec![(vec!["email".to_string()], res.input_scope.clone())]
+        );
+
+        let authorized = AuthorizedResult {
+            unauthorized_fields: vec![],
+            ..result()
+        };
+        let _ = authorized.inspect_unauthorized(|_, _| panic!("no field is unauthorized"));
+    }
+
+    #[test]
+    fn redacted_results_can_be_recovered() {
+        let complete = || AuthorizedResult {
+            unauthorized_fields: vec![],
+            ..result()
+        };
+        assert_eq!(
+            complete().recover_unauthorized(|_, _| unreachable!()),
+            "name"
+        );
+        assert_eq!(complete().recover_unauthorized_or("default".into()), "name");
+
+        let recovered = result().recover_unauthorized(|status, fields| {
+            assert_eq!(status, AuthorizationStatus::Authorized);
+            fields.join(", ")
+        });
+        assert_eq!(recovered, "email");
+        assert_eq!(
+            result().recover_unauthorized_or("default".into()),
+            "default"
+        );
+
+        let unauthorized = AuthorizedResult {
+            status: AuthorizationStatus::UnAuthorized,
+            ..complete()
+        };
+        assert_eq!(
+            unauthorized.recover_unauthorized(|status, _| format!("{status:?}")),
+            "UnAuthorized"
+        );
+    }
+
+    #[test]
+    fn authorized_results_can_be_unwrapped() {
+        assert_eq!(result().unwrap(), "name");
+        assert_eq!(result().expect("unauthorized"), "name");
+        assert_eq!(result().unwrap_or("default".into()), "name");
+
+        let unauthorized = || AuthorizedResult {
+            status: AuthorizationStatus::UnAuthorized,
+            ..result()
+        };
+        assert_eq!(unauthorized().unwrap_or("default".into()), "default");
+        assert_eq!(
+            unauthorized().unwrap_or_else(|status| format!("{status:?}")),
+            "UnAuthorized"
+        );
+
+        let panic = std::panic::catch_unwind(|| unauthorized().unwrap()).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("`read:user`"));
+        assert!(message.contains("[\"email\"]"));
+
+        let panic = std::panic::catch_unwind(|| unauthorized().expect("no access")).unwrap_err();
+        assert_eq!(panic.downcast_ref::<String>().unwrap(), "no access");
+    }
+
+    #[test]
+    fn options_and_results_can_be_transposed() {
+        let some = result()
+            .map_ref(|name| Some(name.clone()))
+            .transpose()
+            .unwrap();
+        assert_eq!(some, result());
+
+        let none = result().map_ref(|_| None::<String>);
+        assert_eq!(none.transpose(), None);
+
+        let ok = result().map_ref(|name| Ok::<_, ()>(name.clone()));
+        assert_eq!(ok.transpose(), Ok(result()));
+
+        let err = result().map_ref(|_| Err::<String, _>("failed"));
+        assert_eq!(err.transpose(), Err("failed"));
+    }
+
+    #[test]
+    fn fields_can_be_filtered_after_authorization() -> Result<(), crate::error::AuthorizedError> {
+        use std::collections::HashSet;
+
+        use crate::prelude::*;
+
+        #[derive(Debug, Clone, Authorized)]
+        struct Article {
+            title: String,
+            body: String,
+            #[authorized(scope = "editor")]
+            draft: String,
+        }
+
+        impl Authorized for Article {}
+
+        let article = Article {
+            title: "title".into(),
+            body: "body".into(),
+            draft: "draft".into(),
+        };
+        let selection: HashSet<&str> = ["title", "draft"].iter().copied().collect();
+
+        let res = Authorizor::authorize(&article, &"reader")?
+            .filter_fields(|field| selection.contains(field));
+        assert_eq!(
+            res.unauthorized_fields,
+            vec!["draft".to_string(), "body".to_string()]
+        );
+        assert_eq!(res.status, AuthorizationStatus::Authorized);
+        assert_eq!(res.inner.body, "body");
+
+        Ok(())
+    }
+}
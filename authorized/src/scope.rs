@@ -1,10 +1,39 @@
+use std::borrow::Cow;
 use std::char;
 use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::fmt;
+use std::ops;
 use std::str;
 
 use std::cmp;
 
+#[cfg(feature = "with_arbitrary")]
+mod arbitrary;
+pub mod ascii_validator;
+#[cfg(feature = "with_compact")]
+mod compact;
+#[cfg(feature = "with_scim")]
+mod compat;
+mod env;
+mod hierarchical;
+mod http;
+mod implication;
+#[cfg(feature = "with_jwt")]
+mod jwt;
+mod policy;
+
+#[cfg(feature = "with_http")]
+pub use self::http::scope_as_bearer;
+#[cfg(feature = "with_compact")]
+pub use compact::CompactScopeError;
+pub use env::ScopeFromEnvError;
+pub use hierarchical::HierarchicalScope;
+pub use policy::{AuthorizationPolicy, AuthorizationPolicyBuilder};
+
+#[cfg(feature = "with_jwt")]
+pub use jwt::ScopeFromJwtError;
+
 /// A scope can be created by a `String`.
 ///
 /// You can use Scope to define rules to validate structures. Validating structure is as simple as
@@ -54,21 +83,46 @@ use std::cmp;
 /// assert!(email_scope.allow_access(&admin_scope));
 /// assert!(password_scope.allow_access(&admin_scope));
 /// ```
-#[derive(Clone, PartialEq, Eq)]
+///
+/// Scope literals can be validated at compile time with the `scope!` macro:
+///
+/// ```
+/// use authorized::prelude::*;
+///
+/// let admin_scope: Scope = scope!("admin");
+/// assert!(scope!("!guest").allow_access(&admin_scope));
+/// ```
+///
+/// ```compile_fail
+/// use authorized::prelude::*;
+///
+/// let scope = scope!("read:\"user\"");
+/// ```
+///
+/// The default scope is the empty scope, which has no restrictions: a resource protected by it
+/// allows access to any scope.
+///
+/// ```
+/// use authorized::prelude::*;
+///
+/// let header_scope: Option<Scope> = None;
+/// let scope = header_scope.unwrap_or_default();
+///
+/// assert_eq!(scope, "".parse().unwrap());
+/// assert!(scope.allow_access(&"guest".parse().unwrap()));
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
 pub struct Scope {
     denied_tokens: HashSet<String>,
     allowed_tokens: HashSet<String>,
 }
 
 impl Scope {
-    fn invalid_scope_char(ch: char) -> bool {
-        match ch {
-            '\x21' => false,
-            ch if ch >= '\x23' && ch <= '\x5b' => false,
-            ch if ch >= '\x5d' && ch <= '\x7e' => false,
-            ' ' => false, // Space seperator is a valid char
-            _ => true,
-        }
+    fn sorted_tokens<'a, I: Iterator<Item = &'a String>>(tokens: I) -> String {
+        let mut tokens: Vec<&str> = tokens.map(String::as_str).collect();
+        tokens.sort_unstable();
+
+        format!("[{}]", tokens.join(", "))
     }
 
     /// Determines if this scope has enough privileges to access some resource requiring the scope
@@ -84,193 +138,1972 @@ impl Scope {
     pub fn allow_access(&self, rhs: &Self) -> bool {
         self <= rhs
     }
-}
 
-/// Expose method to convert the structure into a scope
-pub trait IntoScope {
+    fn with_deny_precedence(
+        allowed_tokens: HashSet<String>,
+        denied_tokens: HashSet<String>,
+    ) -> Self {
+        let allowed_tokens = allowed_tokens
+            .into_iter()
+            .filter(|token| !denied_tokens.contains(token))
+            .collect();
+
+        Self {
+            denied_tokens,
+            allowed_tokens,
+        }
+    }
+
+    /// Consumes this scope, keeping only its allowed tokens.
+    #[must_use]
+    pub fn into_allowed_only(self) -> Self {
+        Self {
+            denied_tokens: HashSet::new(),
+            allowed_tokens: self.allowed_tokens,
+        }
+    }
+
+    /// Consumes this scope, keeping only its denied tokens.
+    #[must_use]
+    pub fn into_denied_only(self) -> Self {
+        Self {
+            denied_tokens: self.denied_tokens,
+            allowed_tokens: HashSet::new(),
+        }
+    }
+
+    /// Produces a scope with the allowed tokens of this scope only.
+    #[must_use]
+    pub fn allowed_scope(&self) -> Self {
+        Self {
+            denied_tokens: HashSet::new(),
+            allowed_tokens: self.allowed_tokens.clone(),
+        }
+    }
+
+    /// Produces a scope with the denied tokens of this scope only.
+    #[must_use]
+    pub fn denied_scope(&self) -> Self {
+        Self {
+            denied_tokens: self.denied_tokens.clone(),
+            allowed_tokens: HashSet::new(),
+        }
+    }
+
+    /// Splits the allowed tokens of this scope between the tokens matching `predicate` and the
+    /// others. Denied tokens are kept in the first scope.
     ///
-    /// # Errors
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
     ///
-    fn into_scope(&self) -> Result<Scope, ParseScopeErr>;
-}
+    /// let scope = "read:user write:user admin !guest".parse::<Scope>().unwrap();
+    /// let (short, long) = scope.partition(|token| token.len() < 6);
+    ///
+    /// assert_eq!(short, "admin !guest".parse().unwrap());
+    /// assert_eq!(long, "read:user write:user".parse().unwrap());
+    /// ```
+    #[must_use]
+    pub fn partition<F: Fn(&str) -> bool>(&self, predicate: F) -> (Self, Self) {
+        let (matching, others) = self
+            .allowed_tokens
+            .iter()
+            .cloned()
+            .partition(|token| predicate(token));
 
-impl<T> IntoScope for T
-where
-    T: AsRef<str>,
-{
-    fn into_scope(&self) -> Result<Scope, ParseScopeErr> {
-        self.as_ref().parse::<Scope>()
+        (
+            Self {
+                denied_tokens: self.denied_tokens.clone(),
+                allowed_tokens: matching,
+            },
+            Self {
+                denied_tokens: HashSet::new(),
+                allowed_tokens: others,
+            },
+        )
     }
-}
 
-impl IntoScope for Scope {
-    fn into_scope(&self) -> Result<Scope, ParseScopeErr> {
-        Ok(self.clone())
+    /// Same as [`partition`](#method.partition) with the tokens starting with `prefix`, e.g.
+    /// `read:` tokens and everything else.
+    #[must_use]
+    pub fn partition_by_prefix(&self, prefix: &str) -> (Self, Self) {
+        self.partition(|token| token.starts_with(prefix))
     }
-}
 
-#[derive(Debug)]
-pub enum ParseScopeErr {
-    /// A character was encountered which is not allowed to appear in scope strings.
+    /// Keeps only the tokens for which `f(token, denied)` returns `true`, like `Vec::retain`.
+    /// Denied tokens are given without their `!` prefix. This is the in place version of
+    /// [`partition`](#method.partition).
     ///
-    /// Scope-tokens are restricted to the following subset of ascii:
-    ///   - The character '!'
-    ///   - The character range '\x32' to '\x5b' which includes numbers and upper case letters
-    ///   - The character range '\x5d' to '\x7e' which includes lower case letters
-    /// Individual scope-tokens are separated by spaces.
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
     ///
-    /// In particular, the characters '\x22' (`"`) and '\x5c' (`\`)  are not allowed.
-    InvalidCharacter(char),
-}
+    /// let mut scope = "read:user write:user !guest".parse::<Scope>().unwrap();
+    /// scope.retain(|token, denied| denied || token.starts_with("read:"));
+    ///
+    /// assert_eq!(scope, "read:user !guest".parse().unwrap());
+    /// ```
+    pub fn retain<F: Fn(&str, bool) -> bool>(&mut self, f: F) {
+        self.allowed_tokens.retain(|token| f(token, false));
+        self.denied_tokens.retain(|token| f(token, true));
+    }
 
-impl str::FromStr for Scope {
-    type Err = ParseScopeErr;
+    /// Keeps only the allowed tokens for which `f` returns `true`. Denied tokens are kept.
+    pub fn retain_allowed<F: Fn(&str) -> bool>(&mut self, f: F) {
+        self.allowed_tokens.retain(|token| f(token));
+    }
 
-    fn from_str(string: &str) -> Result<Self, ParseScopeErr> {
-        if let Some(ch) = string.chars().find(|&ch| Self::invalid_scope_char(ch)) {
-            return Err(ParseScopeErr::InvalidCharacter(ch));
-        }
+    /// Keeps only the denied tokens for which `f` returns `true`. Allowed tokens are kept.
+    pub fn retain_denied<F: Fn(&str) -> bool>(&mut self, f: F) {
+        self.denied_tokens.retain(|token| f(token));
+    }
 
-        let tokens = string.split(' ').filter(|s| !s.is_empty());
+    /// Produces the canonical form of this scope: a token both allowed and denied is only
+    /// kept as denied. Tokens are already deduplicated when parsing.
+    #[must_use]
+    pub fn normalize(&self) -> Self {
+        Self::with_deny_precedence(self.allowed_tokens.clone(), self.denied_tokens.clone())
+    }
 
-        let denied_tokens: HashSet<String> = tokens
-            .clone()
-            .filter_map(|s| {
-                if s.starts_with('!') {
-                    Some(str::to_string(&s[1..]))
-                } else {
-                    None
-                }
-            })
-            .collect();
+    /// Checks if this scope is in its canonical form, see [`normalize`](#method.normalize).
+    #[must_use]
+    pub fn is_normalized(&self) -> bool {
+        self.allowed_tokens.is_disjoint(&self.denied_tokens)
+    }
 
-        let allowed_tokens: HashSet<String> = tokens
-            .clone()
-            .filter_map(|s| {
-                if s.starts_with('!') {
-                    None
-                } else {
-                    Some(str::to_string(s))
-                }
-            })
-            .collect();
+    /// Produces a scope granting the tokens allowed by either scope and denying the tokens denied
+    /// by either scope. Deny wins when a token ends up both allowed and denied.
+    ///
+    /// Also available as `self | other`.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        Self::with_deny_precedence(
+            self.allowed_tokens
+                .union(&other.allowed_tokens)
+                .cloned()
+                .collect(),
+            self.denied_tokens
+                .union(&other.denied_tokens)
+                .cloned()
+                .collect(),
+        )
+    }
 
-        Ok(Self {
-            denied_tokens,
-            allowed_tokens,
-        })
+    /// Produces a scope granting the tokens allowed by both scopes and denying the tokens denied
+    /// by either scope.
+    ///
+    /// Also available as `self & other`.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::with_deny_precedence(
+            self.allowed_tokens
+                .intersection(&other.allowed_tokens)
+                .cloned()
+                .collect(),
+            self.denied_tokens
+                .union(&other.denied_tokens)
+                .cloned()
+                .collect(),
+        )
     }
-}
 
-impl fmt::Display for ParseScopeErr {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        match self {
-            Self::InvalidCharacter(chr) => {
-                write!(fmt, "Encountered invalid character in scope: {}", chr)
-            }
+    /// Produces a scope without the allowed and denied tokens of `other`.
+    ///
+    /// Also available as `self - other`.
+    #[must_use]
+    pub fn subtract(&self, other: &Self) -> Self {
+        Self {
+            denied_tokens: self
+                .denied_tokens
+                .difference(&other.denied_tokens)
+                .cloned()
+                .collect(),
+            allowed_tokens: self
+                .allowed_tokens
+                .difference(&other.allowed_tokens)
+                .cloned()
+                .collect(),
         }
     }
-}
 
-impl fmt::Debug for Scope {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        fmt.debug_tuple("Scope")
-            .field(&self.denied_tokens)
-            .field(&self.allowed_tokens)
-            .finish()
+    /// Produces a scope denying the allowed tokens of this scope and allowing its denied tokens.
+    ///
+    /// Also available as `(!self).into_scope()`, see [`NegatedScope`](struct.NegatedScope.html).
+    #[must_use]
+    pub fn complement(&self) -> Self {
+        Self {
+            denied_tokens: self.allowed_tokens.clone(),
+            allowed_tokens: self.denied_tokens.clone(),
+        }
     }
-}
 
-impl cmp::PartialOrd for Scope {
-    fn partial_cmp(&self, rhs: &Self) -> Option<cmp::Ordering> {
-        if !self.denied_tokens.is_empty() || !rhs.denied_tokens.is_empty() {
-            let lhs_denied_intersect_count =
-                self.denied_tokens.intersection(&rhs.allowed_tokens).count();
-            let rhs_denied_intersect_count =
-                rhs.denied_tokens.intersection(&self.allowed_tokens).count();
+    /// Number of allowed tokens.
+    #[must_use]
+    pub fn allowed_count(&self) -> usize {
+        self.allowed_tokens.len()
+    }
 
-            if lhs_denied_intersect_count > 0 || rhs_denied_intersect_count > 0 {
-                return None;
-            }
-        }
+    /// Number of denied tokens.
+    #[must_use]
+    pub fn denied_count(&self) -> usize {
+        self.denied_tokens.len()
+    }
 
-        let intersect_count = self
-            .allowed_tokens
-            .intersection(&rhs.allowed_tokens)
-            .count();
+    /// Number of allowed and denied tokens.
+    #[must_use]
+    pub fn token_count(&self) -> usize {
+        self.allowed_count() + self.denied_count()
+    }
 
-        if intersect_count == self.allowed_tokens.len()
-            && intersect_count == rhs.allowed_tokens.len()
-        {
-            Some(cmp::Ordering::Equal)
-        } else if intersect_count == self.allowed_tokens.len() {
-            Some(cmp::Ordering::Less)
-        } else if intersect_count == rhs.allowed_tokens.len() {
-            Some(cmp::Ordering::Greater)
-        } else {
-            None
-        }
+    /// Checks if this scope has neither allowed nor denied tokens.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.allowed_tokens.is_empty() && self.denied_tokens.is_empty()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// The power set of the allowed tokens: every scope made of some of them, the empty scope and
+    /// the allowed tokens themselves included. Denied tokens are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scope has more than 20 allowed tokens.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// let scope = "admin read:user write:user".parse::<Scope>().unwrap();
+    /// assert_eq!(scope.subsets().len(), 8);
+    /// ```
+    #[cfg(feature = "scope_algebra")]
+    #[must_use]
+    pub fn subsets(&self) -> Vec<Self> {
+        const MAX_TOKENS: usize = 20;
 
-    #[test]
-    fn scope_can_be_parsed() {
-        let scope_base = "cap1 cap2".parse::<Scope>().unwrap();
-        let scope_less = "cap1".parse::<Scope>().unwrap();
-        let scope_uncmp = "cap1 cap3".parse::<Scope>().unwrap();
-        let user_scope = "user read:user".parse::<Scope>().unwrap();
-        let user_only = "user".parse::<Scope>().unwrap();
-        let guest_only = "guest".parse::<Scope>().unwrap();
-        let read_user = "read:user".parse::<Scope>().unwrap();
-        let not_admin = "!admin".parse::<Scope>().unwrap();
+        let mut tokens: Vec<&String> = self.allowed_tokens.iter().collect();
+        tokens.sort_unstable();
+        assert!(
+            tokens.len() <= MAX_TOKENS,
+            "Can't build the subsets of a scope with more than {:?} allowed tokens",
+            MAX_TOKENS
+        );
 
-        let admin = "admin".parse::<Scope>().unwrap();
-        let admin_read = "admin read:user".parse::<Scope>().unwrap();
+        (0..1_u32 << tokens.len())
+            .map(|mask| Self {
+                denied_tokens: HashSet::new(),
+                allowed_tokens: tokens
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, token)| (*token).clone())
+                    .collect(),
+            })
+            .collect()
+    }
 
-        assert_eq!(
-            scope_base.partial_cmp(&scope_less),
-            Some(cmp::Ordering::Greater)
-        );
-        assert_eq!(
-            scope_less.partial_cmp(&scope_base),
-            Some(cmp::Ordering::Less)
-        );
+    /// Same as [`subsets`](#method.subsets) without the subset holding every allowed token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scope has more than 20 allowed tokens.
+    #[cfg(feature = "scope_algebra")]
+    #[must_use]
+    pub fn strict_subsets(&self) -> Vec<Self> {
+        let mut subsets = self.subsets();
+        subsets.pop();
 
-        assert_eq!(scope_base.partial_cmp(&scope_uncmp), None);
-        assert_eq!(scope_uncmp.partial_cmp(&scope_base), None);
+        subsets
+    }
 
-        assert_eq!(
-            scope_base.partial_cmp(&scope_base),
-            Some(cmp::Ordering::Equal)
-        );
+    pub(crate) fn allowed_tokens(&self) -> impl Iterator<Item = &str> {
+        self.allowed_tokens.iter().map(String::as_str)
+    }
 
-        assert!(scope_base.priviledged_to(&scope_less));
-        assert!(scope_base.priviledged_to(&scope_base));
-        assert!(scope_less.allow_access(&scope_base));
-        assert!(scope_base.allow_access(&scope_base));
+    /// Iterates over every token with a flag set for denied tokens: first the allowed tokens,
+    /// then the denied tokens, both sorted.
+    pub fn iter_tokens(&self) -> impl Iterator<Item = (&str, bool)> {
+        let mut allowed: Vec<&str> = self.allowed_tokens().collect();
+        let mut denied: Vec<&str> = self.denied_tokens.iter().map(String::as_str).collect();
+        allowed.sort_unstable();
+        denied.sort_unstable();
 
-        assert!(!scope_less.priviledged_to(&scope_base));
-        assert!(!scope_base.allow_access(&scope_less));
+        allowed
+            .into_iter()
+            .map(|token| (token, false))
+            .chain(denied.into_iter().map(|token| (token, true)))
+    }
 
-        assert!(!scope_less.priviledged_to(&scope_uncmp));
-        assert!(!scope_base.priviledged_to(&scope_uncmp));
-        assert!(!scope_uncmp.allow_access(&scope_less));
-        assert!(!scope_uncmp.allow_access(&scope_base));
+    /// Same as [`iter_tokens`](#method.iter_tokens) with denied tokens prefixed by `!`, as
+    /// they are displayed.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// let scope = "user !guest admin".parse::<Scope>().unwrap();
+    /// let tokens: Vec<_> = scope.iter_tokens_with_prefix().collect();
+    ///
+    /// assert_eq!(tokens, vec!["admin", "user", "!guest"]);
+    /// assert_eq!(Scope::try_from_iter(tokens).unwrap(), scope);
+    /// ```
+    pub fn iter_tokens_with_prefix(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.iter_tokens().map(|(token, denied)| {
+            if denied {
+                Cow::Owned(format!("!{token}"))
+            } else {
+                Cow::Borrowed(token)
+            }
+        })
+    }
 
-        assert!(user_only.allow_access(&user_scope));
-        assert!(read_user.allow_access(&user_scope));
-        assert!(admin.allow_access(&admin));
-        assert!(not_admin.allow_access(&user_scope));
-        assert!(user_scope.priviledged_to(&not_admin));
+    /// Checks if `token` is a wildcard token, i.e. ends with `*`. A wildcard token like
+    /// `read:*` matches every token starting with `read:`.
+    #[must_use]
+    pub fn is_wildcard_token(token: &str) -> bool {
+        token.ends_with('*')
+    }
 
-        assert!(!not_admin.allow_access(&admin));
-        assert!(not_admin.allow_access(&user_only));
+    fn wildcard_matches(pattern: &str, token: &str) -> bool {
+        pattern
+            .strip_suffix('*')
+            .is_some_and(|prefix| token.starts_with(prefix))
+    }
 
-        assert!(!not_admin.allow_access(&admin_read));
-        assert!(!admin_read.priviledged_to(&not_admin));
+    /// Checks if `token` is one of the allowed tokens of this scope. Wildcards aren't expanded.
+    #[must_use]
+    pub fn contains_token(&self, token: &str) -> bool {
+        self.allowed_tokens.contains(token)
+    }
+
+    /// Checks if `token` is denied by this scope, either exactly or through a denied wildcard
+    /// token.
+    #[must_use]
+    pub fn denies_token(&self, token: &str) -> bool {
+        self.denied_tokens.contains(token)
+            || self
+                .denied_tokens
+                .iter()
+                .any(|denied| Self::wildcard_matches(denied, token))
+    }
+
+    /// Checks if every token of `tokens` is an allowed token of this scope, see
+    /// [`contains_token`](#method.contains_token). Returns `true` for no tokens.
+    #[must_use]
+    pub fn contains_all<I: IntoIterator<Item = S>, S: AsRef<str>>(&self, tokens: I) -> bool {
+        tokens
+            .into_iter()
+            .all(|token| self.contains_token(token.as_ref()))
+    }
+
+    /// Checks if at least one token of `tokens` is an allowed token of this scope, see
+    /// [`contains_token`](#method.contains_token). Returns `false` for no tokens.
+    #[must_use]
+    pub fn contains_any<I: IntoIterator<Item = S>, S: AsRef<str>>(&self, tokens: I) -> bool {
+        tokens
+            .into_iter()
+            .any(|token| self.contains_token(token.as_ref()))
+    }
+
+    /// Checks if every token of `tokens` is denied by this scope, see
+    /// [`denies_token`](#method.denies_token). Returns `true` for no tokens.
+    #[must_use]
+    pub fn denies_all<I: IntoIterator<Item = S>, S: AsRef<str>>(&self, tokens: I) -> bool {
+        tokens
+            .into_iter()
+            .all(|token| self.denies_token(token.as_ref()))
+    }
+
+    /// Checks if at least one token of `tokens` is denied by this scope, see
+    /// [`denies_token`](#method.denies_token). Returns `false` for no tokens.
+    #[must_use]
+    pub fn denies_any<I: IntoIterator<Item = S>, S: AsRef<str>>(&self, tokens: I) -> bool {
+        tokens
+            .into_iter()
+            .any(|token| self.denies_token(token.as_ref()))
+    }
+
+    /// Checks if `token` is covered by an allowed token of this scope, either exactly or
+    /// through a wildcard token. A denied token always wins over an allowed wildcard.
+    ///
+    /// Wildcards are only interpreted by the token inspection methods, comparisons like
+    /// [`allow_access`](#method.allow_access) compare tokens literally.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// let scope = "read:* !read:secret".parse::<Scope>().unwrap();
+    ///
+    /// assert!(scope.wildcard_covers("read:user"));
+    /// assert!(!scope.wildcard_covers("read:secret"));
+    /// assert!(!scope.wildcard_covers("write:user"));
+    /// ```
+    #[must_use]
+    pub fn wildcard_covers(&self, token: &str) -> bool {
+        if self.denies_token(token) {
+            return false;
+        }
+
+        self.contains_token(token)
+            || self
+                .allowed_tokens
+                .iter()
+                .any(|allowed| Self::wildcard_matches(allowed, token))
+    }
+
+    /// Number of allowed tokens minus the number of denied tokens.
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn effective_level(&self) -> isize {
+        self.allowed_tokens.len() as isize - self.denied_tokens.len() as isize
+    }
+
+    /// Wraps a clone of this scope in a [`PrivilegedScope`](struct.PrivilegedScope.html) to
+    /// get a total order over scopes.
+    #[must_use]
+    pub fn ord_by_privilege(&self) -> PrivilegedScope {
+        self.clone().into_privileged()
+    }
+
+    /// Borrows this scope as a [`CardinalityOrdScope`](struct.CardinalityOrdScope.html) to get
+    /// a total order over scopes based on their token counts only.
+    #[must_use]
+    pub fn by_cardinality(&self) -> CardinalityOrdScope<'_> {
+        CardinalityOrdScope(self)
+    }
+
+    /// Wraps this scope in a [`PrivilegedScope`](struct.PrivilegedScope.html) to get a total
+    /// order over scopes.
+    #[must_use]
+    pub fn into_privileged(self) -> PrivilegedScope {
+        PrivilegedScope(self)
+    }
+
+    /// Builds a scope from `(token, granted)` pairs: granted tokens are allowed, the others are
+    /// denied.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a token contains an invalid character. Tokens can't contain spaces, nor start with
+    /// `!` as the denial is given by the pair.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// let scope = Scope::from_role_pairs(vec![("admin", true), ("guest", false)]).unwrap();
+    ///
+    /// assert_eq!(scope, "admin !guest".parse().unwrap());
+    /// ```
+    pub fn from_role_pairs<I, S>(pairs: I) -> Result<Self, ParseScopeErr>
+    where
+        I: IntoIterator<Item = (S, bool)>,
+        S: AsRef<str>,
+    {
+        let mut denied_tokens = HashSet::new();
+        let mut allowed_tokens = HashSet::new();
+
+        for (token, granted) in pairs {
+            let token = token.as_ref();
+            ascii_validator::validate_scope_string(token)?;
+
+            if token.contains(' ') {
+                return Err(ParseScopeErr::InvalidCharacter(' '));
+            }
+
+            if token.starts_with('!') {
+                return Err(ParseScopeErr::InvalidCharacter('!'));
+            }
+
+            if token.is_empty() {
+                continue;
+            }
+
+            if granted {
+                allowed_tokens.insert(token.to_string());
+            } else {
+                denied_tokens.insert(token.to_string());
+            }
+        }
+
+        Ok(Self {
+            denied_tokens,
+            allowed_tokens,
+        })
+    }
+
+    /// Builds a scope from already validated tokens.
+    ///
+    /// This is called by the code generated by `scope!`.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn from_tokens(allowed: &[&str], denied: &[&str]) -> Self {
+        Self {
+            denied_tokens: denied.iter().map(|token| (*token).to_string()).collect(),
+            allowed_tokens: allowed.iter().map(|token| (*token).to_string()).collect(),
+        }
+    }
+
+    /// Build a scope from single tokens, `!` prefixed tokens being denied. Empty tokens are
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// Fails on the first token containing an invalid character or a space.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// let scope = Scope::try_from_iter(vec!["admin", "!guest"]).unwrap();
+    /// assert_eq!(scope, "admin !guest".parse().unwrap());
+    /// ```
+    pub fn try_from_iter<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        iter: I,
+    ) -> Result<Self, ParseScopeErr> {
+        let mut scope = Self::default();
+
+        for token in iter {
+            scope.insert_single_token(token.as_ref())?;
+        }
+
+        Ok(scope)
+    }
+
+    /// Same as [`try_from_iter`](#method.try_from_iter) for an iterator of results, stopping
+    /// at the first error.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the first upstream error or the first invalid token.
+    pub fn try_collect_scope<S: AsRef<str>, E, I: Iterator<Item = Result<S, E>>>(
+        iter: I,
+    ) -> Result<Self, ScopeCollectError<E>> {
+        let mut scope = Self::default();
+
+        for token in iter {
+            let token = token.map_err(ScopeCollectError::Upstream)?;
+            scope.insert_single_token(token.as_ref())?;
+        }
+
+        Ok(scope)
+    }
+
+    fn insert_single_token(&mut self, token: &str) -> Result<(), ParseScopeErr> {
+        ascii_validator::validate_scope_string(token)?;
+
+        if token.contains(' ') {
+            return Err(ParseScopeErr::InvalidCharacter(' '));
+        }
+
+        if let Some(denied) = token.strip_prefix('!') {
+            self.denied_tokens.insert(denied.to_string());
+        } else if !token.is_empty() {
+            self.allowed_tokens.insert(token.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Parses every input, returning the parsed scopes and the errors separately.
+    #[must_use]
+    pub fn parse_batch(inputs: &[&str]) -> (Vec<Self>, Vec<ParseScopeErr>) {
+        let mut scopes = Vec::with_capacity(inputs.len());
+        let mut errors = Vec::new();
+
+        for input in inputs {
+            match input.parse::<Self>() {
+                Ok(scope) => scopes.push(scope),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        (scopes, errors)
+    }
+
+    /// Parses every input, failing with all the errors if any input is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns every [`ParseScopeErr`](enum.ParseScopeErr.html) encountered.
+    pub fn parse_batch_strict(inputs: &[&str]) -> Result<Vec<Self>, Vec<ParseScopeErr>> {
+        let (scopes, errors) = Self::parse_batch(inputs);
+
+        if errors.is_empty() {
+            Ok(scopes)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Same as [`parse_batch_strict`](#method.parse_batch_strict), to validate lists of scopes
+    /// which must all be valid, e.g. at startup.
+    ///
+    /// # Errors
+    ///
+    /// Returns every [`ParseScopeErr`](enum.ParseScopeErr.html) encountered.
+    pub fn parse_all(inputs: &[&str]) -> Result<Vec<Self>, Vec<ParseScopeErr>> {
+        Self::parse_batch_strict(inputs)
+    }
+
+    /// Parses the first valid input, skipping the invalid ones.
+    #[must_use]
+    pub fn parse_first_valid(inputs: &[&str]) -> Option<Self> {
+        inputs.iter().find_map(|input| input.parse::<Self>().ok())
+    }
+
+    /// Parses the most privileged of the valid inputs, according to
+    /// [`PrivilegedScope`](struct.PrivilegedScope.html). Falls back to an empty scope when no
+    /// input is valid.
+    #[must_use]
+    pub fn parse_best(inputs: &[&str]) -> Self {
+        inputs
+            .iter()
+            .filter_map(|input| input.parse::<Self>().ok())
+            .max_by_key(Self::ord_by_privilege)
+            .unwrap_or_default()
+    }
+
+    /// Checks if this scope imposes more restrictions than `other`: it denies more tokens, or
+    /// its denied tokens are a strict superset of the denied tokens of `other`. Allowed tokens
+    /// are ignored.
+    #[must_use]
+    pub fn is_more_restrictive_than(&self, other: &Self) -> bool {
+        self.denied_tokens.len() > other.denied_tokens.len()
+            || (self.denied_tokens.is_superset(&other.denied_tokens)
+                && self.denied_tokens != other.denied_tokens)
+    }
+
+    /// Checks if both scopes deny the same tokens, regardless of their allowed tokens.
+    #[must_use]
+    pub fn has_same_restrictions_as(&self, other: &Self) -> bool {
+        self.denied_tokens == other.denied_tokens
+    }
+
+    /// Checks if a token denied by one of the scopes is allowed by the other one. Such scopes
+    /// can't be compared, unlike scopes that are only incomparable because their allowed tokens
+    /// differ.
+    #[must_use]
+    pub fn has_conflict_with(&self, other: &Self) -> bool {
+        !self.denied_tokens.is_disjoint(&other.allowed_tokens)
+            || !other.denied_tokens.is_disjoint(&self.allowed_tokens)
+    }
+
+    /// Describes the conflicts found by [`has_conflict_with`](#method.has_conflict_with), or
+    /// `None` if there is no conflict. `self` is the left scope and `other` the right one.
+    #[must_use]
+    pub fn explain_conflict(&self, other: &Self) -> Option<String> {
+        let mut denied_left: Vec<&String> = self
+            .denied_tokens
+            .intersection(&other.allowed_tokens)
+            .collect();
+        denied_left.sort_unstable();
+        let mut denied_right: Vec<&String> = other
+            .denied_tokens
+            .intersection(&self.allowed_tokens)
+            .collect();
+        denied_right.sort_unstable();
+
+        let conflicts: Vec<String> = denied_left
+            .into_iter()
+            .map(|token| format!("scope '{token}' is denied in left but allowed in right"))
+            .chain(
+                denied_right
+                    .into_iter()
+                    .map(|token| format!("scope '{token}' is denied in right but allowed in left")),
+            )
+            .collect();
+
+        if conflicts.is_empty() {
+            None
+        } else {
+            Some(conflicts.join(", "))
+        }
+    }
+
+    /// Explains in a human readable way why a resource protected by this scope allows or denies
+    /// access to `authorizer`.
+    ///
+    /// This is only meant for debugging and tests, the output format isn't stable.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn debug_explain(&self, authorizer: &Self) -> String {
+        let decision = if self.allow_access(authorizer) {
+            "granted"
+        } else {
+            "denied"
+        };
+
+        let denied_by = Self::sorted_tokens(
+            self.denied_tokens
+                .intersection(&authorizer.allowed_tokens)
+                .chain(authorizer.denied_tokens.intersection(&self.allowed_tokens)),
+        );
+
+        format!(
+            "Access {}: resource requires {} but authorizer only has {}. Denied by: {}. Missing tokens: {}.",
+            decision,
+            Self::sorted_tokens(self.allowed_tokens.iter()),
+            Self::sorted_tokens(authorizer.allowed_tokens.iter()),
+            denied_by,
+            Self::sorted_tokens(self.allowed_tokens.difference(&authorizer.allowed_tokens)),
+        )
+    }
+
+    /// Produces a new scope keeping the allowed tokens of this scope and adding the denied
+    /// tokens of `deny_scope`. Allowed tokens of `deny_scope` are ignored.
+    ///
+    /// When a token ends up both allowed and denied, deny wins and the token is removed from
+    /// the allowed tokens.
+    #[must_use]
+    pub fn merge_deny(&self, deny_scope: &Self) -> Self {
+        let denied_tokens: HashSet<String> = self
+            .denied_tokens
+            .union(&deny_scope.denied_tokens)
+            .cloned()
+            .collect();
+
+        let allowed_tokens: HashSet<String> = self
+            .allowed_tokens
+            .difference(&denied_tokens)
+            .cloned()
+            .collect();
+
+        Self {
+            denied_tokens,
+            allowed_tokens,
+        }
+    }
+}
+
+/// Scope union, see [`Scope::union`](struct.Scope.html#method.union).
+///
+/// # Examples
+/// ```
+/// use authorized::prelude::*;
+///
+/// let user = "user read:user".parse::<Scope>().unwrap();
+/// let role = "billing".parse::<Scope>().unwrap();
+/// let blacklist = "read:user".parse::<Scope>().unwrap();
+///
+/// let effective = (user | role) & !blacklist;
+/// assert_eq!(effective, "billing user !read:user".parse().unwrap());
+/// ```
+impl ops::BitOr for Scope {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(&rhs)
+    }
+}
+
+/// Scope intersection, see [`Scope::intersection`](struct.Scope.html#method.intersection).
+impl ops::BitAnd for Scope {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.intersection(&rhs)
+    }
+}
+
+/// Scope difference, see [`Scope::subtract`](struct.Scope.html#method.subtract).
+impl ops::Sub for Scope {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.subtract(&rhs)
+    }
+}
+
+/// Scope negation, produces a [`NegatedScope`](struct.NegatedScope.html).
+impl ops::Not for Scope {
+    type Output = NegatedScope;
+
+    fn not(self) -> NegatedScope {
+        NegatedScope(self)
+    }
+}
+
+/// A negated [`Scope`](struct.Scope.html), produced by `!scope`.
+///
+/// Intersecting a scope with a negated scope restricts it: `scope & !blacklist` keeps the
+/// allowed tokens of `scope` and denies the allowed tokens of `blacklist`. The denied tokens of
+/// `blacklist` are ignored.
+///
+/// # Examples
+/// ```
+/// use authorized::prelude::*;
+///
+/// let user = "user read:user".parse::<Scope>().unwrap();
+/// let blacklist = "read:user".parse::<Scope>().unwrap();
+///
+/// assert_eq!(user & !blacklist.clone(), "user !read:user".parse().unwrap());
+/// assert_eq!((!blacklist.clone()).into_scope(), blacklist.complement());
+/// assert_eq!(!!blacklist.clone(), blacklist);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NegatedScope(Scope);
+
+impl NegatedScope {
+    /// The complement of the negated scope, see [`Scope::complement`](struct.Scope.html#method.complement).
+    #[must_use]
+    pub fn into_scope(self) -> Scope {
+        self.0.complement()
+    }
+}
+
+impl ops::Not for NegatedScope {
+    type Output = Scope;
+
+    fn not(self) -> Scope {
+        self.0
+    }
+}
+
+/// Scope restriction, see [`NegatedScope`](struct.NegatedScope.html).
+impl ops::BitAnd<NegatedScope> for Scope {
+    type Output = Self;
+
+    fn bitand(self, rhs: NegatedScope) -> Self {
+        let mut denied_tokens = self.denied_tokens;
+        denied_tokens.extend(rhs.0.allowed_tokens);
+
+        Self::with_deny_precedence(self.allowed_tokens, denied_tokens)
+    }
+}
+
+/// Add the tokens of a scope string, `!` prefixed tokens are added to the denied tokens.
+///
+/// The result is the same as parsing the concatenation of both scope strings.
+///
+/// # Panics
+///
+/// Panics if the string contains an invalid character.
+///
+/// # Examples
+/// ```
+/// use authorized::prelude::*;
+///
+/// let mut scope = "user".parse::<Scope>().unwrap();
+/// scope += "admin !guest";
+///
+/// assert_eq!(scope, "user admin !guest".parse().unwrap());
+/// assert_eq!(scope + "read:user", "user admin !guest read:user".parse().unwrap());
+/// ```
+impl ops::AddAssign<&str> for Scope {
+    fn add_assign(&mut self, rhs: &str) {
+        let rhs = rhs
+            .parse::<Self>()
+            .unwrap_or_else(|error| panic!("Can't add {:?} to a scope: {}", rhs, error));
+
+        self.allowed_tokens.extend(rhs.allowed_tokens);
+        self.denied_tokens.extend(rhs.denied_tokens);
+    }
+}
+
+/// Add the tokens of a scope string, see
+/// [`AddAssign<&str>`](struct.Scope.html#impl-AddAssign%3C%26str%3E-for-Scope).
+impl ops::Add<&str> for Scope {
+    type Output = Self;
+
+    fn add(mut self, rhs: &str) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+/// A [`Scope`](struct.Scope.html) ordered by privilege.
+///
+/// `Scope` only implements `PartialOrd`. `PrivilegedScope` provides a total order, useful to
+/// sort scopes from the least to the most privileged: scopes are ordered by
+/// [`allowed_count`](struct.Scope.html#method.allowed_count), then by
+/// [`effective_level`](struct.Scope.html#method.effective_level), then by their canonical string
+/// representation.
+///
+/// The order agrees with `Scope::partial_cmp` when it compares two scopes: a scope is only less
+/// than another one when its allowed tokens are a strict subset, so it has fewer of them. Being
+/// a single key, the order stays transitive, which `sort` and `max` rely on.
+///
+/// # Examples
+/// ```
+/// use authorized::prelude::*;
+///
+/// let mut scopes: Vec<Scope> = vec![
+///     "admin user".parse().unwrap(),
+///     "guest".parse().unwrap(),
+///     "admin".parse().unwrap(),
+/// ];
+/// scopes.sort_by_key(Scope::ord_by_privilege);
+///
+/// assert_eq!(scopes[2], "admin user".parse().unwrap());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrivilegedScope(Scope);
+
+impl PrivilegedScope {
+    #[must_use]
+    pub fn into_scope(self) -> Scope {
+        self.0
+    }
+}
+
+impl cmp::Ord for PrivilegedScope {
+    fn cmp(&self, rhs: &Self) -> cmp::Ordering {
+        self.0
+            .allowed_count()
+            .cmp(&rhs.0.allowed_count())
+            .then_with(|| self.0.by_cardinality().cmp(&rhs.0.by_cardinality()))
+    }
+}
+
+impl cmp::PartialOrd for PrivilegedScope {
+    fn partial_cmp(&self, rhs: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+/// A borrowed [`Scope`](struct.Scope.html) ordered by
+/// [`effective_level`](struct.Scope.html#method.effective_level), then by its canonical string
+/// representation.
+///
+/// Unlike [`PrivilegedScope`](struct.PrivilegedScope.html) the number of allowed tokens comes
+/// second: a scope with more denied tokens can be less than a scope it includes.
+///
+/// # Examples
+/// ```
+/// use authorized::prelude::*;
+///
+/// let mut scopes: Vec<Scope> = vec![
+///     "admin user".parse().unwrap(),
+///     "guest".parse().unwrap(),
+///     "admin !guest".parse().unwrap(),
+/// ];
+/// scopes.sort_by(|lhs, rhs| lhs.by_cardinality().cmp(&rhs.by_cardinality()));
+///
+/// assert_eq!(scopes[0], "admin !guest".parse().unwrap());
+/// assert_eq!(scopes[2], "admin user".parse().unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CardinalityOrdScope<'a>(&'a Scope);
+
+impl CardinalityOrdScope<'_> {
+    #[must_use]
+    pub fn as_scope(&self) -> &Scope {
+        self.0
+    }
+}
+
+impl cmp::Ord for CardinalityOrdScope<'_> {
+    fn cmp(&self, rhs: &Self) -> cmp::Ordering {
+        self.0
+            .effective_level()
+            .cmp(&rhs.0.effective_level())
+            .then_with(|| self.0.to_string().cmp(&rhs.0.to_string()))
+    }
+}
+
+impl cmp::PartialOrd for CardinalityOrdScope<'_> {
+    fn partial_cmp(&self, rhs: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+/// Expose method to convert the structure into a scope
+pub trait IntoScope {
+    ///
+    /// # Errors
+    ///
+    #[allow(clippy::wrong_self_convention)]
+    fn into_scope(&self) -> Result<Scope, ParseScopeErr>;
+}
+
+impl<T> IntoScope for T
+where
+    T: AsRef<str> + ?Sized,
+{
+    fn into_scope(&self) -> Result<Scope, ParseScopeErr> {
+        self.as_ref().parse::<Scope>()
+    }
+}
+
+impl IntoScope for Scope {
+    fn into_scope(&self) -> Result<Scope, ParseScopeErr> {
+        Ok(self.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseScopeErr {
+    /// A character was encountered which is not allowed to appear in scope strings.
+    ///
+    /// Scope-tokens are restricted to the following subset of ascii:
+    ///   - The character '!'
+    ///   - The character range '\x32' to '\x5b' which includes numbers and upper case letters
+    ///   - The character range '\x5d' to '\x7e' which includes lower case letters
+    ///
+    /// Individual scope-tokens are separated by spaces.
+    ///
+    /// In particular, the characters '\x22' (`"`) and '\x5c' (`\`)  are not allowed.
+    InvalidCharacter(char),
+}
+
+impl str::FromStr for Scope {
+    type Err = ParseScopeErr;
+
+    fn from_str(string: &str) -> Result<Self, ParseScopeErr> {
+        ascii_validator::validate_scope_string(string)?;
+
+        let tokens = string.split(' ').filter(|s| !s.is_empty());
+
+        let denied_tokens: HashSet<String> = tokens
+            .clone()
+            .filter_map(|s| s.strip_prefix('!').map(str::to_string))
+            .collect();
+
+        let allowed_tokens: HashSet<String> = tokens
+            .clone()
+            .filter_map(|s| {
+                if s.starts_with('!') {
+                    None
+                } else {
+                    Some(str::to_string(s))
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            denied_tokens,
+            allowed_tokens,
+        })
+    }
+}
+
+/// Parses the string, like `str::parse`.
+impl TryFrom<&str> for Scope {
+    type Error = ParseScopeErr;
+
+    fn try_from(string: &str) -> Result<Self, ParseScopeErr> {
+        string.parse()
+    }
+}
+
+/// Parses the string, like `str::parse`.
+impl TryFrom<String> for Scope {
+    type Error = ParseScopeErr;
+
+    fn try_from(string: String) -> Result<Self, ParseScopeErr> {
+        string.parse()
+    }
+}
+
+/// The canonical string of the scope, like
+/// [`Display`](struct.Scope.html#impl-Display-for-Scope).
+impl From<&Scope> for String {
+    fn from(scope: &Scope) -> Self {
+        scope.to_string()
+    }
+}
+
+/// The canonical string of the scope, like
+/// [`Display`](struct.Scope.html#impl-Display-for-Scope).
+impl From<Scope> for String {
+    fn from(scope: Scope) -> Self {
+        scope.to_string()
+    }
+}
+
+impl fmt::Display for ParseScopeErr {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::InvalidCharacter(chr) => {
+                write!(fmt, "Encountered invalid character in scope: {chr}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseScopeErr {}
+
+/// Error of [`Scope::try_collect_scope`](struct.Scope.html#method.try_collect_scope).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScopeCollectError<E> {
+    /// An item of the iterator is an error.
+    Upstream(E),
+    ParseScopeError(ParseScopeErr),
+}
+
+impl<E> From<ParseScopeErr> for ScopeCollectError<E> {
+    fn from(error: ParseScopeErr) -> Self {
+        Self::ParseScopeError(error)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for ScopeCollectError<E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::Upstream(error) => error.fmt(fmt),
+            Self::ParseScopeError(error) => error.fmt(fmt),
+        }
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for ScopeCollectError<E> {}
+
+/// Find the scope [`priviledged_to`](struct.Scope.html#method.priviledged_to) every other scope
+/// of `scopes`.
+///
+/// Scopes are only partially ordered, so `None` is returned when no scope is privileged to all
+/// the others, e.g. when two scopes are incomparable, as well as when `scopes` is empty. See
+/// [`Scope::ord_by_privilege`](struct.Scope.html#method.ord_by_privilege) for a total order.
+///
+/// # Examples
+/// ```
+/// use authorized::prelude::*;
+/// use authorized::scope;
+///
+/// let scopes = ["admin read:user", "admin", "read:user"];
+/// let max = scope::maximize(scopes.iter().map(|s| s.parse().unwrap()));
+/// assert_eq!(max, Some("admin read:user".parse().unwrap()));
+///
+/// let scopes = ["admin", "user"];
+/// assert_eq!(scope::maximize(scopes.iter().map(|s| s.parse().unwrap())), None);
+/// ```
+pub fn maximize(scopes: impl IntoIterator<Item = Scope>) -> Option<Scope> {
+    let scopes: Vec<Scope> = scopes.into_iter().collect();
+
+    scopes
+        .iter()
+        .find(|scope| scopes.iter().all(|other| scope.priviledged_to(other)))
+        .cloned()
+}
+
+/// Find the scope every other scope of `scopes` is
+/// [`priviledged_to`](struct.Scope.html#method.priviledged_to).
+///
+/// Returns `None` when there is no such scope or when `scopes` is empty, see
+/// [`maximize`](fn.maximize.html).
+pub fn minimize(scopes: impl IntoIterator<Item = Scope>) -> Option<Scope> {
+    let scopes: Vec<Scope> = scopes.into_iter().collect();
+
+    scopes
+        .iter()
+        .find(|scope| scopes.iter().all(|other| other.priviledged_to(scope)))
+        .cloned()
+}
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Scope")
+            .field(&self.denied_tokens)
+            .field(&self.allowed_tokens)
+            .finish()
+    }
+}
+
+/// Formats the scope in its canonical form: allowed tokens sorted alphabetically followed by
+/// denied tokens sorted alphabetically, all separated by a space.
+impl fmt::Display for Scope {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut allowed: Vec<&String> = self.allowed_tokens.iter().collect();
+        allowed.sort_unstable();
+        let mut denied: Vec<&String> = self.denied_tokens.iter().collect();
+        denied.sort_unstable();
+
+        let tokens: Vec<String> = allowed
+            .into_iter()
+            .cloned()
+            .chain(denied.into_iter().map(|token| format!("!{token}")))
+            .collect();
+
+        write!(fmt, "{}", tokens.join(" "))
+    }
+}
+
+impl cmp::PartialOrd for Scope {
+    fn partial_cmp(&self, rhs: &Self) -> Option<cmp::Ordering> {
+        if !self.denied_tokens.is_empty() || !rhs.denied_tokens.is_empty() {
+            let lhs_denied_intersect_count =
+                self.denied_tokens.intersection(&rhs.allowed_tokens).count();
+            let rhs_denied_intersect_count =
+                rhs.denied_tokens.intersection(&self.allowed_tokens).count();
+
+            if lhs_denied_intersect_count > 0 || rhs_denied_intersect_count > 0 {
+                return None;
+            }
+        }
+
+        let intersect_count = self
+            .allowed_tokens
+            .intersection(&rhs.allowed_tokens)
+            .count();
+
+        if intersect_count == self.allowed_tokens.len()
+            && intersect_count == rhs.allowed_tokens.len()
+        {
+            Some(cmp::Ordering::Equal)
+        } else if intersect_count == self.allowed_tokens.len() {
+            Some(cmp::Ordering::Less)
+        } else if intersect_count == rhs.allowed_tokens.len() {
+            Some(cmp::Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(unused_variables)]
+    fn scope_can_be_parsed() {
+        let scope_base = "cap1 cap2".parse::<Scope>().unwrap();
+        let scope_less = "cap1".parse::<Scope>().unwrap();
+        let scope_uncmp = "cap1 cap3".parse::<Scope>().unwrap();
+        let user_scope = "user read:user".parse::<Scope>().unwrap();
+        let user_only = "user".parse::<Scope>().unwrap();
+        let guest_only = "guest".parse::<Scope>().unwrap();
+        let read_user = "read:user".parse::<Scope>().unwrap();
+        let not_admin = "!admin".parse::<Scope>().unwrap();
+
+        let admin = "admin".parse::<Scope>().unwrap();
+        let admin_read = "admin read:user".parse::<Scope>().unwrap();
+
+        assert_eq!(
+            scope_base.partial_cmp(&scope_less),
+            Some(cmp::Ordering::Greater)
+        );
+        assert_eq!(
+            scope_less.partial_cmp(&scope_base),
+            Some(cmp::Ordering::Less)
+        );
+
+        assert_eq!(scope_base.partial_cmp(&scope_uncmp), None);
+        assert_eq!(scope_uncmp.partial_cmp(&scope_base), None);
+
+        assert_eq!(
+            scope_base.partial_cmp(&scope_base),
+            Some(cmp::Ordering::Equal)
+        );
+
+        assert!(scope_base.priviledged_to(&scope_less));
+        assert!(scope_base.priviledged_to(&scope_base));
+        assert!(scope_less.allow_access(&scope_base));
+        assert!(scope_base.allow_access(&scope_base));
+
+        assert!(!scope_less.priviledged_to(&scope_base));
+        assert!(!scope_base.allow_access(&scope_less));
+
+        assert!(!scope_less.priviledged_to(&scope_uncmp));
+        assert!(!scope_base.priviledged_to(&scope_uncmp));
+        assert!(!scope_uncmp.allow_access(&scope_less));
+        assert!(!scope_uncmp.allow_access(&scope_base));
+
+        assert!(user_only.allow_access(&user_scope));
+        assert!(read_user.allow_access(&user_scope));
+        assert!(admin.allow_access(&admin));
+        assert!(not_admin.allow_access(&user_scope));
+        assert!(user_scope.priviledged_to(&not_admin));
+
+        assert!(!not_admin.allow_access(&admin));
+        assert!(not_admin.allow_access(&user_only));
+
+        assert!(!not_admin.allow_access(&admin_read));
+        assert!(!admin_read.priviledged_to(&not_admin));
+    }
+
+    #[test]
+    fn scope_display_is_canonical() {
+        let scope = "user !guest admin !banned read:user"
+            .parse::<Scope>()
+            .unwrap();
+
+        assert_eq!(scope.to_string(), "admin read:user user !banned !guest");
+        assert_eq!(scope.to_string().parse::<Scope>().unwrap(), scope);
+        assert_eq!("".parse::<Scope>().unwrap().to_string(), "");
+    }
+
+    #[test]
+    fn normalized_scopes_let_deny_win() {
+        let scope = Scope::from_tokens(&["admin", "user", "admin"], &["admin", "guest"]);
+        assert!(!scope.is_normalized());
+
+        let normalized = scope.normalize();
+        assert!(normalized.is_normalized());
+        assert_eq!(normalized, "user !admin !guest".parse::<Scope>().unwrap());
+        assert_eq!(normalized.normalize(), normalized);
+        assert_eq!(normalized.to_string().parse::<Scope>().unwrap(), normalized);
+    }
+
+    #[test]
+    fn scopes_can_be_split() {
+        let scope = "user read:user !admin".parse::<Scope>().unwrap();
+
+        assert_eq!(scope.allowed_scope(), "user read:user".parse().unwrap());
+        assert_eq!(scope.denied_scope(), "!admin".parse().unwrap());
+        assert_eq!(
+            scope.allowed_scope().union(&scope.denied_scope()),
+            scope.normalize()
+        );
+
+        let policy = "!read:user".parse::<Scope>().unwrap();
+        assert_eq!(
+            scope.allowed_scope().merge_deny(&policy.denied_scope()),
+            "user !read:user".parse().unwrap()
+        );
+
+        assert_eq!(scope.clone().into_allowed_only(), scope.allowed_scope());
+        assert_eq!(scope.clone().into_denied_only(), scope.denied_scope());
+    }
+
+    #[test]
+    fn tokens_can_be_counted() {
+        let scope = "admin read:user admin !guest".parse::<Scope>().unwrap();
+
+        assert_eq!(scope.allowed_count(), 2);
+        assert_eq!(scope.denied_count(), 1);
+        assert_eq!(scope.token_count(), 3);
+        assert_eq!("".parse::<Scope>().unwrap().token_count(), 0);
+    }
+
+    #[test]
+    fn scopes_can_be_parsed_in_batch() {
+        let (scopes, errors) = Scope::parse_batch(&["admin", "read:\"user\"", "user", "b\\d"]);
+        assert_eq!(
+            scopes,
+            vec!["admin".parse::<Scope>().unwrap(), "user".parse().unwrap()]
+        );
+        assert_eq!(
+            errors,
+            vec![
+                ParseScopeErr::InvalidCharacter('"'),
+                ParseScopeErr::InvalidCharacter('\\')
+            ]
+        );
+
+        assert_eq!(Scope::parse_batch(&[]), (vec![], vec![]));
+
+        assert_eq!(
+            Scope::parse_batch_strict(&["admin", "user"]).unwrap().len(),
+            2
+        );
+        assert_eq!(
+            Scope::parse_batch_strict(&["admin", "\"", "\""]),
+            Err(vec![
+                ParseScopeErr::InvalidCharacter('"'),
+                ParseScopeErr::InvalidCharacter('"')
+            ])
+        );
+        assert_eq!(Scope::parse_batch_strict(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn tokens_can_be_retained() {
+        use std::cell::RefCell;
+
+        let calls = RefCell::new(Vec::new());
+        let mut scope = "read:user write:user admin !guest !banned"
+            .parse::<Scope>()
+            .unwrap();
+        scope.retain(|token, denied| {
+            calls.borrow_mut().push((token.to_string(), denied));
+            token != "admin" && token != "banned"
+        });
+
+        let mut calls = calls.into_inner();
+        calls.sort();
+        assert_eq!(
+            calls,
+            vec![
+                ("admin".to_string(), false),
+                ("banned".to_string(), true),
+                ("guest".to_string(), true),
+                ("read:user".to_string(), false),
+                ("write:user".to_string(), false),
+            ]
+        );
+        assert_eq!(scope, "read:user write:user !guest".parse().unwrap());
+
+        scope.retain_allowed(|token| token.starts_with("read:"));
+        assert_eq!(scope, "read:user !guest".parse().unwrap());
+        scope.retain_denied(|_| false);
+        assert_eq!(scope, "read:user".parse().unwrap());
+        assert!(!scope.is_empty());
+
+        scope.retain(|_, _| false);
+        assert!(scope.is_empty());
+        assert!("".parse::<Scope>().unwrap().is_empty());
+        assert!(!"!guest".parse::<Scope>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn scopes_convert_from_and_to_strings() {
+        use std::convert::TryInto;
+
+        let scope: Scope = "admin !guest".try_into().unwrap();
+        assert_eq!(scope, "admin !guest".parse().unwrap());
+        assert_eq!(
+            Scope::try_from(String::from("user admin")),
+            "admin user".parse()
+        );
+        assert_eq!(
+            Scope::try_from("read:\"user\""),
+            Err(ParseScopeErr::InvalidCharacter('"'))
+        );
+
+        let string: String = (&scope).into();
+        assert_eq!(string, "admin !guest");
+        let string: String = scope.clone().into();
+        assert_eq!(Scope::try_from(string), Ok(scope));
+    }
+
+    #[test]
+    fn scopes_can_be_parsed_all_first_or_best() {
+        let empty = "".parse::<Scope>().unwrap();
+        let admin = "admin".parse::<Scope>().unwrap();
+
+        assert_eq!(Scope::parse_all(&[]), Ok(vec![]));
+        assert_eq!(Scope::parse_first_valid(&[]), None);
+        assert_eq!(Scope::parse_best(&[]), empty);
+
+        assert_eq!(
+            Scope::parse_all(&["admin", "user"]),
+            Ok(vec![admin.clone(), "user".parse().unwrap()])
+        );
+        assert_eq!(Scope::parse_first_valid(&["admin", "user"]), Some(admin));
+        assert_eq!(
+            Scope::parse_best(&["admin", "admin user", "guest"]),
+            "admin user".parse().unwrap()
+        );
+
+        let mixed = ["b\\d", "guest", "\"", "admin user"];
+        assert_eq!(
+            Scope::parse_all(&mixed),
+            Err(vec![
+                ParseScopeErr::InvalidCharacter('\\'),
+                ParseScopeErr::InvalidCharacter('"')
+            ])
+        );
+        assert_eq!(
+            Scope::parse_first_valid(&mixed),
+            Some("guest".parse().unwrap())
+        );
+        assert_eq!(Scope::parse_best(&mixed), "admin user".parse().unwrap());
+
+        assert_eq!(Scope::parse_first_valid(&["\""]), None);
+        assert_eq!(Scope::parse_best(&["\""]), empty);
+    }
+
+    #[test]
+    fn best_scope_does_not_depend_on_input_order() {
+        fn check_permutations(inputs: &mut [&str], k: usize, expected: &Scope) {
+            if k == inputs.len() {
+                assert_eq!(&Scope::parse_best(inputs), expected, "{inputs:?}");
+                return;
+            }
+            for i in k..inputs.len() {
+                inputs.swap(k, i);
+                check_permutations(inputs, k + 1, expected);
+                inputs.swap(k, i);
+            }
+        }
+
+        let mut inputs = ["x y", "!x !y z", "x", "guest", "\""];
+        let expected = "x y".parse::<Scope>().unwrap();
+
+        check_permutations(&mut inputs, 0, &expected);
+
+        // `partial_cmp` ranks the scope with denied tokens above the scope it includes.
+        let mut inputs = ["a b !x !y", "a", "b"];
+        let expected = "a b !x !y".parse::<Scope>().unwrap();
+
+        check_permutations(&mut inputs, 0, &expected);
+    }
+
+    #[test]
+    fn scopes_can_be_built_from_role_pairs() {
+        let roles = vec![("admin".to_string(), true), ("guest".to_string(), false)];
+        assert_eq!(
+            Scope::from_role_pairs(roles),
+            Ok("admin !guest".parse::<Scope>().unwrap())
+        );
+
+        assert_eq!(
+            Scope::from_role_pairs([("read:\"user\"", true)]),
+            Err(ParseScopeErr::InvalidCharacter('"'))
+        );
+        assert_eq!(
+            Scope::from_role_pairs([("admin user", false)]),
+            Err(ParseScopeErr::InvalidCharacter(' '))
+        );
+        assert_eq!(
+            Scope::from_role_pairs([("!admin", true)]),
+            Err(ParseScopeErr::InvalidCharacter('!'))
+        );
+        assert_eq!(
+            Scope::from_role_pairs([("!admin", false)]),
+            Err(ParseScopeErr::InvalidCharacter('!'))
+        );
+        assert_eq!(
+            Scope::from_role_pairs(Vec::<(&str, bool)>::new()),
+            Ok("".parse::<Scope>().unwrap())
+        );
+    }
+
+    #[test]
+    fn scope_macro_builds_validated_scopes() {
+        use crate::prelude::scope;
+
+        assert_eq!(
+            scope!("admin read:user !guest"),
+            "admin read:user !guest".parse::<Scope>().unwrap()
+        );
+        assert_eq!(scope!(" user  !admin "), "user !admin".parse().unwrap());
+        assert_eq!(scope!(""), "".parse().unwrap());
+    }
+
+    #[test]
+    fn privileged_scopes_are_totally_ordered() {
+        let parse = |s: &str| s.parse::<Scope>().unwrap();
+
+        let mut scopes = vec![
+            parse("admin read:user"),
+            parse("guest"),
+            parse("user !admin"),
+            parse("admin"),
+            parse(""),
+            parse("billing"),
+        ];
+        scopes.sort_by_key(Scope::ord_by_privilege);
+
+        assert_eq!(
+            scopes,
+            vec![
+                parse(""),
+                parse("user !admin"),
+                parse("admin"),
+                parse("billing"),
+                parse("guest"),
+                parse("admin read:user"),
+            ]
+        );
+
+        // Same allowed tokens but different denied tokens are not considered equal.
+        let denied = parse("user !guest").into_privileged();
+        let allowed = parse("user").into_privileged();
+        assert_eq!(denied.cmp(&allowed), cmp::Ordering::Less);
+        assert_eq!(allowed.into_scope(), parse("user"));
+    }
+
+    #[test]
+    fn privileged_order_is_transitive() {
+        let parse = |s: &str| s.parse::<Scope>().unwrap().into_privileged();
+
+        // `a < b` by inclusion, `b` and `c` are incomparable, `c < a` by effective level.
+        let a = parse("x");
+        let b = parse("x y");
+        let c = parse("!x !y z");
+
+        assert_eq!(a.cmp(&b), cmp::Ordering::Less);
+        assert_eq!(c.cmp(&a), cmp::Ordering::Less);
+        assert_eq!(c.cmp(&b), cmp::Ordering::Less);
+    }
+
+    #[test]
+    fn privileged_order_agrees_with_partial_cmp() {
+        let inputs = ["a b !x !y", "a", "a b", "", "!a", "b !x", "a !x !y !z", "c"];
+
+        for lhs in &inputs {
+            for rhs in &inputs {
+                let (lhs, rhs) = (lhs.parse::<Scope>().unwrap(), rhs.parse::<Scope>().unwrap());
+
+                if let Some(ordering @ (cmp::Ordering::Less | cmp::Ordering::Greater)) =
+                    lhs.partial_cmp(&rhs)
+                {
+                    assert_eq!(
+                        lhs.ord_by_privilege().cmp(&rhs.ord_by_privilege()),
+                        ordering,
+                        "{lhs} and {rhs}"
+                    );
+                }
+            }
+        }
+
+        let denied = "a b !x !y".parse::<Scope>().unwrap();
+        let a = "a".parse::<Scope>().unwrap();
+        assert_eq!(denied.partial_cmp(&a), Some(cmp::Ordering::Greater));
+        assert!(denied.ord_by_privilege() > a.ord_by_privilege());
+        assert!(denied.by_cardinality() < a.by_cardinality());
+    }
+
+    #[test]
+    fn extremes_exist_only_for_comparable_scopes() {
+        let parse = |inputs: &[&str]| {
+            inputs
+                .iter()
+                .map(|s| s.parse::<Scope>().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let scopes = parse(&["admin", "admin read:user", "read:user"]);
+        assert_eq!(
+            maximize(scopes.clone()),
+            Some("admin read:user".parse().unwrap())
+        );
+        assert_eq!(minimize(scopes), None);
+
+        let scopes = parse(&["admin read:user", "", "admin"]);
+        assert_eq!(minimize(scopes), Some("".parse().unwrap()));
+
+        // `user` is incomparable with the other scopes.
+        let scopes = parse(&["admin read:user", "admin", "user"]);
+        assert_eq!(maximize(scopes.clone()), None);
+        assert_eq!(minimize(scopes), None);
+
+        assert_eq!(maximize(vec![]), None);
+        assert_eq!(minimize(vec![]), None);
+    }
+
+    #[test]
+    fn scopes_can_be_combined_with_operators() {
+        let parse = |s: &str| s.parse::<Scope>().unwrap();
+
+        let user = parse("user read:user !admin");
+        let role = parse("billing read:user");
+
+        assert_eq!(user.union(&role), parse("billing read:user user !admin"));
+        assert_eq!(user.clone() | role.clone(), user.union(&role));
+        assert_eq!(
+            parse("admin") | parse("!admin"),
+            parse("!admin"),
+            "deny wins"
+        );
+
+        assert_eq!(user.intersection(&role), parse("read:user !admin"));
+        assert_eq!(user.clone() & role.clone(), user.intersection(&role));
+
+        assert_eq!(user.subtract(&role), parse("user !admin"));
+        assert_eq!(user.clone() - parse("!admin"), parse("user read:user"));
+
+        assert_eq!(user.complement(), parse("admin !user !read:user"));
+        assert_eq!((!user.clone()).into_scope(), user.complement());
+        assert_eq!(!!user.clone(), user);
+
+        let blacklist = parse("read:user !billing");
+        assert_eq!(
+            (user.clone() | role.clone()) & !blacklist,
+            parse("billing user !admin !read:user")
+        );
+
+        assert_eq!(
+            (user | role) & parse("read:user billing"),
+            parse("billing read:user !admin")
+        );
+    }
+
+    #[test]
+    fn tokens_can_be_inspected_with_wildcards() {
+        let scope = "read:* admin !read:secret !write:*"
+            .parse::<Scope>()
+            .unwrap();
+
+        assert!(Scope::is_wildcard_token("read:*"));
+        assert!(!Scope::is_wildcard_token("read:user"));
+
+        assert!(scope.contains_token("admin"));
+        assert!(!scope.contains_token("read:user"));
+        assert!(scope.wildcard_covers("admin"));
+        assert!(scope.wildcard_covers("read:user"));
+        assert!(scope.wildcard_covers("read:"));
+        assert!(!scope.wildcard_covers("reader"));
+        assert!(!scope.wildcard_covers("guest"));
+
+        assert!(scope.denies_token("read:secret"));
+        assert!(scope.denies_token("write:user"));
+        assert!(!scope.wildcard_covers("read:secret"));
+        assert!(!scope.denies_token("read:user"));
+
+        let everything = "*".parse::<Scope>().unwrap();
+        assert!(everything.wildcard_covers("anything"));
+    }
+
+    #[test]
+    fn restrictions_are_compared_on_denied_tokens() {
+        let guest = "user !guest".parse::<Scope>().unwrap();
+        let guest_billing = "!guest !billing".parse::<Scope>().unwrap();
+        let admin_billing = "admin !admin !billing".parse::<Scope>().unwrap();
+        let open = "admin user".parse::<Scope>().unwrap();
+
+        assert!(guest_billing.is_more_restrictive_than(&guest));
+        assert!(!guest.is_more_restrictive_than(&guest_billing));
+        assert!(guest.is_more_restrictive_than(&open));
+        assert!(!open.is_more_restrictive_than(&open));
+
+        // Overlapping denials of the same size are as restrictive as each other.
+        assert!(!guest_billing.is_more_restrictive_than(&admin_billing));
+        assert!(!admin_billing.is_more_restrictive_than(&guest_billing));
+        assert!(!guest_billing.has_same_restrictions_as(&admin_billing));
+
+        assert!(guest.has_same_restrictions_as(&"!guest admin".parse().unwrap()));
+        assert!(open.has_same_restrictions_as(&Scope::from_tokens(&[], &[])));
+    }
+
+    #[test]
+    fn conflicts_are_distinguished_from_disjoint_scopes() {
+        let not_admin = "user !admin".parse::<Scope>().unwrap();
+        let admin = "admin".parse::<Scope>().unwrap();
+        let billing = "billing".parse::<Scope>().unwrap();
+
+        assert_eq!(not_admin.partial_cmp(&admin), None);
+        assert!(not_admin.has_conflict_with(&admin));
+        assert!(admin.has_conflict_with(&not_admin));
+        assert_eq!(
+            not_admin.explain_conflict(&admin).as_deref(),
+            Some("scope 'admin' is denied in left but allowed in right")
+        );
+        assert_eq!(
+            admin.explain_conflict(&not_admin).as_deref(),
+            Some("scope 'admin' is denied in right but allowed in left")
+        );
+
+        assert_eq!(admin.partial_cmp(&billing), None);
+        assert!(!admin.has_conflict_with(&billing));
+        assert_eq!(admin.explain_conflict(&billing), None);
+    }
+
+    #[test]
+    fn debug_explain_lists_missing_and_denied_tokens() {
+        let admin = "admin".parse::<Scope>().unwrap();
+        let user = "user read:user".parse::<Scope>().unwrap();
+        let not_admin = "!admin".parse::<Scope>().unwrap();
+
+        assert_eq!(
+            admin.debug_explain(&user),
+            "Access denied: resource requires [admin] but authorizer only has [read:user, user]. Denied by: []. Missing tokens: [admin]."
+        );
+
+        assert_eq!(
+            not_admin.debug_explain(&admin),
+            "Access denied: resource requires [] but authorizer only has [admin]. Denied by: [admin]. Missing tokens: []."
+        );
+
+        assert!(user.debug_explain(&user).starts_with("Access granted"));
+    }
+
+    #[test]
+    fn multiple_tokens_can_be_checked_at_once() {
+        let scope = "admin read:user !guest !write:*".parse::<Scope>().unwrap();
+        let none: [&str; 0] = [];
+
+        assert!(scope.contains_all(none));
+        assert!(!scope.contains_any(none));
+        assert!(scope.denies_all(none));
+        assert!(!scope.denies_any(none));
+
+        assert!(scope.contains_all(["admin"]));
+        assert!(scope.contains_all(vec!["admin", "read:user", "admin"]));
+        assert!(!scope.contains_all(["admin", "guest"]));
+        assert!(scope.contains_any(["guest", "admin"]));
+        assert!(!scope.contains_any(["guest", "write:user"]));
+
+        assert!(scope.denies_all(["guest", "write:user"]));
+        assert!(!scope.denies_all(["guest", "admin"]));
+        assert!(scope.denies_any(vec!["admin".to_string(), "guest".to_string()]));
+        assert!(!scope.denies_any(["admin", "read:user"]));
+    }
+
+    #[cfg(feature = "scope_algebra")]
+    #[test]
+    fn subsets_form_the_power_set_of_allowed_tokens() {
+        let scope = "admin read:user write:user !guest"
+            .parse::<Scope>()
+            .unwrap();
+
+        let subsets = scope.subsets();
+        assert_eq!(subsets.len(), 1 << scope.allowed_count());
+        assert_eq!(subsets[0], Scope::from_tokens(&[], &[]));
+        assert_eq!(
+            subsets.last(),
+            Some(&"admin read:user write:user".parse().unwrap())
+        );
+        assert!(subsets.iter().all(|subset| subset.denied_count() == 0));
+        assert!(subsets
+            .iter()
+            .all(|subset| scope.contains_all(&subset.allowed_tokens)));
+
+        let strict = scope.strict_subsets();
+        assert_eq!(strict.len(), 7);
+        assert!(!strict.contains(&scope.into_allowed_only()));
+
+        assert_eq!(Scope::from_tokens(&[], &[]).subsets().len(), 1);
+        assert!(Scope::from_tokens(&[], &[]).strict_subsets().is_empty());
+
+        let tokens: Vec<String> = (0..21).map(|i| format!("token{i}")).collect();
+        let large = tokens.join(" ").parse::<Scope>().unwrap();
+        assert!(std::panic::catch_unwind(|| large.subsets()).is_err());
+    }
+
+    #[test]
+    fn scopes_can_be_collected_from_tokens() {
+        assert_eq!(
+            Scope::try_from_iter(vec!["admin", "!guest", "", "read:user"]),
+            Ok("admin !guest read:user".parse().unwrap())
+        );
+        assert_eq!(
+            Scope::try_from_iter(vec!["admin".to_string(), "read:\"user\"".to_string()]),
+            Err(ParseScopeErr::InvalidCharacter('"'))
+        );
+        assert_eq!(
+            Scope::try_from_iter(["admin user"]),
+            Err(ParseScopeErr::InvalidCharacter(' '))
+        );
+        assert_eq!(
+            Scope::try_from_iter(Vec::<&str>::new()),
+            Ok(Scope::from_tokens(&[], &[]))
+        );
+
+        let lines = vec![Ok("admin"), Ok("!guest")];
+        assert_eq!(
+            Scope::try_collect_scope(lines.into_iter()),
+            Ok::<_, ScopeCollectError<&str>>("admin !guest".parse().unwrap())
+        );
+        let lines = vec![Ok("admin"), Err("read failed"), Ok("back\\slash")];
+        assert_eq!(
+            Scope::try_collect_scope(lines.into_iter()),
+            Err(ScopeCollectError::Upstream("read failed"))
+        );
+        let lines = vec![Ok::<_, &str>("back\\slash")];
+        assert_eq!(
+            Scope::try_collect_scope(lines.into_iter()),
+            Err(ScopeCollectError::ParseScopeError(
+                ParseScopeErr::InvalidCharacter('\\')
+            ))
+        );
+    }
+
+    #[test]
+    fn tokens_are_iterated_in_a_stable_order() {
+        let scope = "write:user !guest admin !billing read:user"
+            .parse::<Scope>()
+            .unwrap();
+
+        let tokens: Vec<(&str, bool)> = scope.iter_tokens().collect();
+        assert_eq!(
+            tokens,
+            vec![
+                ("admin", false),
+                ("read:user", false),
+                ("write:user", false),
+                ("billing", true),
+                ("guest", true),
+            ]
+        );
+        assert_eq!(scope.iter_tokens().collect::<Vec<_>>(), tokens);
+
+        let prefixed: Vec<_> = scope.iter_tokens_with_prefix().collect();
+        assert_eq!(
+            prefixed.join(" "),
+            "admin read:user write:user !billing !guest"
+        );
+        assert_eq!(prefixed.join(" ").parse::<Scope>().unwrap(), scope);
+        assert_eq!(Scope::try_from_iter(prefixed).unwrap(), scope);
+
+        assert_eq!(Scope::from_tokens(&[], &[]).iter_tokens().count(), 0);
+    }
+
+    #[test]
+    fn partitioned_scopes_union_to_the_original() {
+        for input in &[
+            "",
+            "admin",
+            "!guest",
+            "read:user write:user read:profile admin !guest",
+        ] {
+            let scope = input.parse::<Scope>().unwrap();
+
+            let (read, others) = scope.partition_by_prefix("read:");
+            assert!(read
+                .iter_tokens()
+                .all(|(token, denied)| denied || token.starts_with("read:")));
+            assert!(!others
+                .iter_tokens()
+                .any(|(token, denied)| denied || token.starts_with("read:")));
+            assert_eq!(read.union(&others), scope);
+
+            let (matching, others) = scope.partition(|token| token.contains('i'));
+            assert_eq!(matching.denied_scope(), scope.denied_scope());
+            assert_eq!(matching | others, scope);
+        }
+
+        let (all, none) = "admin user".parse::<Scope>().unwrap().partition(|_| true);
+        assert_eq!(all, "admin user".parse().unwrap());
+        assert_eq!(none, "".parse().unwrap());
+    }
+
+    #[test]
+    fn scopes_can_be_sorted_by_cardinality() {
+        let parse = |scopes: &[&str]| -> Vec<Scope> {
+            scopes.iter().map(|scope| scope.parse().unwrap()).collect()
+        };
+        let expected = parse(&["!guest", "", "admin !guest", "guest", "admin user"]);
+
+        for input in &[
+            parse(&["admin user", "guest", "", "admin !guest", "!guest"]),
+            parse(&["guest", "admin !guest", "admin user", "!guest", ""]),
+        ] {
+            let mut scopes = input.clone();
+            scopes.sort_by(|lhs, rhs| lhs.by_cardinality().cmp(&rhs.by_cardinality()));
+            assert_eq!(scopes, expected);
+        }
+
+        let admin = "admin".parse::<Scope>().unwrap();
+        assert_eq!(admin.by_cardinality().as_scope(), &admin);
+        assert_eq!(
+            admin
+                .by_cardinality()
+                .cmp(&"guest".parse::<Scope>().unwrap().by_cardinality()),
+            cmp::Ordering::Less
+        );
+    }
+
+    #[test]
+    fn string_tokens_can_be_added() {
+        let mut scope = "user !guest".parse::<Scope>().unwrap();
+        scope += "admin";
+        scope += "!billing read:user";
+
+        assert_eq!(
+            scope,
+            "user !guest admin !billing read:user".parse().unwrap()
+        );
+        assert_eq!(
+            scope.clone() + "" + "user",
+            "user !guest admin !billing read:user user".parse().unwrap()
+        );
+
+        let result =
+            std::panic::catch_unwind(|| "user".parse::<Scope>().unwrap() + "read:\"user\"");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn merge_deny_only_adopts_denied_tokens() {
+        let admin = "admin".parse::<Scope>().unwrap();
+        let not_admin = "!admin".parse::<Scope>().unwrap();
+
+        let merged = admin.merge_deny(&not_admin);
+        assert!(merged.allowed_tokens.is_empty());
+        assert!(merged.denied_tokens.contains("admin"));
+
+        let user = "user read:user".parse::<Scope>().unwrap();
+        let restriction = "guest !read:user !write:user".parse::<Scope>().unwrap();
+
+        let merged = user.merge_deny(&restriction);
+        assert_eq!(
+            merged,
+            "user !read:user !write:user".parse::<Scope>().unwrap()
+        );
+        assert!(!merged.allowed_tokens.contains("guest"));
     }
 }
@@ -0,0 +1,81 @@
+//! Generation of arbitrary scopes for fuzzing.
+//!
+//! Generated scopes only contain valid tokens, so they round-trip through their string
+//! representation, and are [normalized](../struct.Scope.html#method.normalize). See the `fuzz`
+//! directory for the fuzz targets.
+
+use std::collections::HashSet;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::ascii_validator::VALID_SCOPE_CHARS;
+use super::Scope;
+
+/// The longest generated token.
+const MAX_TOKEN_LEN: usize = 16;
+
+fn token(u: &mut Unstructured<'_>) -> Result<String> {
+    // The space separates tokens and a leading `!` denies them, neither can be part of a token.
+    let chars: Vec<u8> = VALID_SCOPE_CHARS
+        .bytes()
+        .filter(|&byte| byte != b' ' && byte != b'!')
+        .collect();
+    let len = u.int_in_range(1..=MAX_TOKEN_LEN)?;
+
+    (0..len)
+        .map(|_| u.choose(&chars).map(|&byte| char::from(byte)))
+        .collect()
+}
+
+fn tokens(u: &mut Unstructured<'_>) -> Result<HashSet<String>> {
+    let count = u.arbitrary_len::<u8>()?;
+
+    (0..count).map(|_| token(u)).collect()
+}
+
+impl<'a> Arbitrary<'a> for Scope {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let allowed_tokens = tokens(u)?;
+        let denied_tokens = tokens(u)?;
+
+        Ok(Self::with_deny_precedence(allowed_tokens, denied_tokens))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::*;
+
+    /// Pseudo random bytes, generated with a linear congruential generator.
+    fn bytes(seed: u32, len: usize) -> Vec<u8> {
+        let mut state = seed;
+
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                state.to_be_bytes()[1]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn arbitrary_scopes_round_trip() {
+        for seed in 0..200 {
+            let bytes = bytes(seed, 512);
+            let mut u = Unstructured::new(&bytes);
+            let scope = Scope::arbitrary(&mut u).unwrap();
+            let other = Scope::arbitrary(&mut u).unwrap();
+
+            assert_eq!(scope.to_string().parse::<Scope>(), Ok(scope.clone()));
+            assert!(scope.is_normalized());
+            assert_eq!(scope.partial_cmp(&scope), Some(Ordering::Equal));
+            assert!(scope.allow_access(&scope));
+            assert_eq!(
+                scope.partial_cmp(&other).map(Ordering::reverse),
+                other.partial_cmp(&scope)
+            );
+        }
+    }
+}
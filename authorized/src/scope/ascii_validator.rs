@@ -0,0 +1,121 @@
+//! Validation of the scope character set.
+//!
+//! Scope strings are restricted to a subset of printable ASCII, see
+//! [`ParseScopeErr::InvalidCharacter`](../enum.ParseScopeErr.html#variant.InvalidCharacter).
+//! These functions let you check user input without building a
+//! [`Scope`](../struct.Scope.html).
+//!
+//! # Examples
+//! ```
+//! use authorized::scope::ascii_validator::{is_valid_scope_token, validate_scope_string};
+//!
+//! assert!(is_valid_scope_token("read:user"));
+//! assert!(!is_valid_scope_token("read:user admin"));
+//! assert!(validate_scope_string("read:user admin !guest").is_ok());
+//! assert!(validate_scope_string("read:\"user\"").is_err());
+//! ```
+//!
+//! The `scope` attributes of `#[derive(Authorized)]` are checked the same way while the macro
+//! is expanded, an invalid character is reported as a compile error on the attribute:
+//!
+//! ```compile_fail
+//! use authorized::prelude::*;
+//!
+//! #[derive(Authorized)]
+//! struct User {
+//!     #[authorized(scope = "read:\\user")]
+//!     email: String,
+//! }
+//! ```
+
+use super::ParseScopeErr;
+
+/// Every character allowed in a scope string, the space separator included.
+pub const VALID_SCOPE_CHARS: &str =
+    " !#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
+/// Checks if `ch` can appear in a scope string. The space separator is valid.
+#[must_use]
+pub fn is_valid_scope_char(ch: char) -> bool {
+    matches!(ch, ' ' | '\x21' | '\x23'..='\x5b' | '\x5d'..='\x7e')
+}
+
+/// Checks if `s` is a single scope token: a non empty string of valid characters without
+/// spaces. Denied tokens, like `!admin`, are valid tokens.
+#[must_use]
+pub fn is_valid_scope_token(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|ch| ch != ' ' && is_valid_scope_char(ch))
+}
+
+/// Checks that `s` can be parsed as a [`Scope`](../struct.Scope.html).
+///
+/// # Errors
+///
+/// Returns the first invalid character, like `Scope::from_str` does.
+pub fn validate_scope_string(s: &str) -> Result<(), ParseScopeErr> {
+    match s.chars().find(|&ch| !is_valid_scope_char(ch)) {
+        Some(ch) => Err(ParseScopeErr::InvalidCharacter(ch)),
+        None => Ok(()),
+    }
+}
+
+// `VALID_SCOPE_CHARS` can't drift from the validated set.
+const _: () = assert!(valid_scope_chars(VALID_SCOPE_CHARS.as_bytes()));
+
+/// Checks in a const context that `s` only contains valid scope characters.
+#[must_use]
+pub(crate) const fn valid_scope_chars(s: &[u8]) -> bool {
+    let mut i = 0;
+
+    while i < s.len() {
+        match s[i] {
+            b' ' | b'\x21' | b'\x23'..=b'\x5b' | b'\x5d'..=b'\x7e' => i += 1,
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_chars_match_the_documented_set() {
+        let valid: String = (0..=0x7f_u8)
+            .map(char::from)
+            .filter(|&ch| is_valid_scope_char(ch))
+            .collect();
+
+        assert_eq!(valid, VALID_SCOPE_CHARS);
+        assert!(!is_valid_scope_char('\u{e9}'));
+        assert!(VALID_SCOPE_CHARS
+            .bytes()
+            .all(|byte| valid_scope_chars(&[byte])));
+    }
+
+    #[test]
+    fn tokens_and_strings_are_validated() {
+        assert!(is_valid_scope_token("!admin"));
+        assert!(!is_valid_scope_token(""));
+        assert!(!is_valid_scope_token("admin user"));
+        assert!(!is_valid_scope_token("back\\slash"));
+
+        assert_eq!(validate_scope_string(""), Ok(()));
+        assert_eq!(
+            validate_scope_string("admin read:\"user\""),
+            Err(ParseScopeErr::InvalidCharacter('"'))
+        );
+    }
+
+    #[test]
+    fn scope_chars_are_validated_in_const_context() {
+        const _: () = assert!(valid_scope_chars(b"admin read:user !guest"));
+
+        assert!(!valid_scope_chars(b"read:\"user\""));
+        assert!(!valid_scope_chars(b"back\\slash"));
+        assert!(!valid_scope_chars("caf\u{e9}".as_bytes()));
+        assert!(valid_scope_chars(b""));
+    }
+}
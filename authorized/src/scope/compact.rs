@@ -0,0 +1,209 @@
+//! Compact binary encoding of scopes.
+//!
+//! Scopes stored in cookies or compact tokens can be encoded as length prefixed bytes instead
+//! of their string representation, which is faster to decode as tokens don't need to be split.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use super::ascii_validator::is_valid_scope_token;
+use super::Scope;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CompactScopeError {
+    /// The bytes end in the middle of the encoding.
+    UnexpectedEnd,
+    /// Bytes are left after the last token.
+    TrailingBytes(usize),
+    /// A token isn't valid UTF-8, contains an invalid character, or starts with `!`.
+    InvalidToken(Vec<u8>),
+}
+
+impl fmt::Display for CompactScopeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::UnexpectedEnd => write!(fmt, "Unexpected end of compact scope"),
+            Self::TrailingBytes(count) => {
+                write!(fmt, "Compact scope has {count} trailing bytes")
+            }
+            Self::InvalidToken(token) => write!(
+                fmt,
+                "Invalid token in compact scope: {}",
+                String::from_utf8_lossy(token)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CompactScopeError {}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CompactScopeError> {
+        if self.bytes.len() < len {
+            return Err(CompactScopeError::UnexpectedEnd);
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, CompactScopeError> {
+        self.take(1).map(|byte| byte[0])
+    }
+
+    fn token(&mut self) -> Result<String, CompactScopeError> {
+        let len = self.byte()?;
+        let token = self.take(usize::from(len))?;
+
+        match std::str::from_utf8(token) {
+            Ok(token) if is_valid_scope_token(token) && !token.starts_with('!') => {
+                Ok(token.to_string())
+            }
+            _ => Err(CompactScopeError::InvalidToken(token.to_vec())),
+        }
+    }
+}
+
+impl Scope {
+    /// Encode this scope in a compact binary format: the count of allowed tokens and the count
+    /// of denied tokens as bytes, then each token, without its `!` prefix, prefixed by its
+    /// length as a byte. Each group of tokens is sorted so equal scopes have the same encoding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scope has more than 255 allowed or denied tokens, or a token longer than
+    /// 255 bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// let scope = "read:user !admin".parse::<Scope>().unwrap();
+    /// let bytes = scope.to_compact_representation();
+    ///
+    /// assert_eq!(bytes, b"\x01\x01\x09read:user\x05admin");
+    /// assert_eq!(Scope::from_compact_representation(&bytes).unwrap(), scope);
+    /// ```
+    #[must_use]
+    pub fn to_compact_representation(&self) -> Vec<u8> {
+        fn count(len: usize) -> u8 {
+            u8::try_from(len)
+                .unwrap_or_else(|_| panic!("Too many tokens for a compact scope: {:?}", len))
+        }
+
+        let mut allowed: Vec<&String> = self.allowed_tokens.iter().collect();
+        let mut denied: Vec<&String> = self.denied_tokens.iter().collect();
+        allowed.sort_unstable();
+        denied.sort_unstable();
+
+        let mut bytes = vec![count(allowed.len()), count(denied.len())];
+
+        for token in allowed.into_iter().chain(denied) {
+            let len = u8::try_from(token.len())
+                .unwrap_or_else(|_| panic!("Token too long for a compact scope: {:?}", token));
+
+            bytes.push(len);
+            bytes.extend_from_slice(token.as_bytes());
+        }
+
+        bytes
+    }
+
+    /// Decode a scope encoded by
+    /// [`to_compact_representation`](#method.to_compact_representation).
+    ///
+    /// # Errors
+    ///
+    /// Fails if the bytes are truncated, if bytes are left after the last token, or if a token
+    /// is invalid.
+    pub fn from_compact_representation(bytes: &[u8]) -> Result<Self, CompactScopeError> {
+        let mut reader = Reader { bytes };
+        let allowed_count = reader.byte()?;
+        let denied_count = reader.byte()?;
+
+        let allowed_tokens = (0..allowed_count)
+            .map(|_| reader.token())
+            .collect::<Result<_, _>>()?;
+        let denied_tokens = (0..denied_count)
+            .map(|_| reader.token())
+            .collect::<Result<_, _>>()?;
+
+        if !reader.bytes.is_empty() {
+            return Err(CompactScopeError::TrailingBytes(reader.bytes.len()));
+        }
+
+        Ok(Self {
+            denied_tokens,
+            allowed_tokens,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_round_trip() {
+        for input in &[
+            "",
+            "admin",
+            "!guest",
+            "read:user write:user read:profile !admin",
+            "read:* !write:*",
+        ] {
+            let scope = input.parse::<Scope>().unwrap();
+            let bytes = scope.to_compact_representation();
+
+            assert_eq!(Scope::from_compact_representation(&bytes).unwrap(), scope);
+        }
+
+        let scope = "read:user write:user read:profile !admin"
+            .parse::<Scope>()
+            .unwrap();
+        assert_eq!(scope.to_compact_representation().len(), 42);
+        assert_eq!(
+            "".parse::<Scope>().unwrap().to_compact_representation(),
+            vec![0, 0]
+        );
+    }
+
+    #[test]
+    fn invalid_bytes_are_rejected() {
+        let decode = Scope::from_compact_representation;
+
+        assert_eq!(decode(b""), Err(CompactScopeError::UnexpectedEnd));
+        assert_eq!(decode(b"\x01"), Err(CompactScopeError::UnexpectedEnd));
+        assert_eq!(decode(b"\x01\x00"), Err(CompactScopeError::UnexpectedEnd));
+        assert_eq!(
+            decode(b"\x01\x00\x05adm"),
+            Err(CompactScopeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            decode(b"\x01\x00\x05admin!"),
+            Err(CompactScopeError::TrailingBytes(1))
+        );
+        assert_eq!(
+            decode(b"\x00\x01\x06!admin"),
+            Err(CompactScopeError::InvalidToken(b"!admin".to_vec()))
+        );
+        assert_eq!(
+            decode(b"\x01\x00\x00"),
+            Err(CompactScopeError::InvalidToken(vec![]))
+        );
+        assert_eq!(
+            decode(b"\x01\x00\x02\xc3\x28"),
+            Err(CompactScopeError::InvalidToken(vec![0xc3, 0x28]))
+        );
+        assert_eq!(
+            decode(b"\x01\x00\x03a b"),
+            Err(CompactScopeError::InvalidToken(b"a b".to_vec()))
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! SCIM 2.0 scope compatibility.
+//!
+//! SCIM scopes are comma separated and usually reference resources with URNs such as
+//! `urn:ietf:params:scim:schemas:core:2.0:User`. Inside a `Scope` the common
+//! `urn:ietf:params:scim:` prefix is dropped, giving tokens like `schemas:core:2.0:User`.
+
+use super::{ParseScopeErr, Scope};
+
+const SCIM_URN_PREFIX: &str = "urn:ietf:params:scim:";
+const SCIM_URN_KINDS: [&str; 2] = ["schemas:", "api:"];
+
+fn from_scim_token(token: &str) -> String {
+    let (denied, token) = match token.strip_prefix('!') {
+        Some(token) => ("!", token),
+        None => ("", token),
+    };
+
+    let token = match token.get(..SCIM_URN_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(SCIM_URN_PREFIX) => {
+            &token[SCIM_URN_PREFIX.len()..]
+        }
+        _ => token,
+    };
+
+    format!("{denied}{token}")
+}
+
+fn to_scim_token(token: &str) -> String {
+    if SCIM_URN_KINDS.iter().any(|kind| token.starts_with(kind)) {
+        format!("{SCIM_URN_PREFIX}{token}")
+    } else {
+        token.to_string()
+    }
+}
+
+impl Scope {
+    /// Formats the scope as a comma separated SCIM scope, restoring the
+    /// `urn:ietf:params:scim:` prefix of SCIM resource tokens.
+    ///
+    /// Allowed tokens come first, then denied tokens, each sorted alphabetically.
+    #[must_use]
+    pub fn to_scim_scope(&self) -> String {
+        let mut allowed: Vec<&String> = self.allowed_tokens.iter().collect();
+        allowed.sort_unstable();
+        let mut denied: Vec<&String> = self.denied_tokens.iter().collect();
+        denied.sort_unstable();
+
+        allowed
+            .into_iter()
+            .map(|token| to_scim_token(token))
+            .chain(
+                denied
+                    .into_iter()
+                    .map(|token| format!("!{}", to_scim_token(token))),
+            )
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Parses a comma separated SCIM scope, normalizing SCIM URNs to the crate's token format.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseScopeErr`](enum.ParseScopeErr.html) if a token contains an invalid
+    /// character, including whitespace inside a token.
+    pub fn from_scim_scope(s: &str) -> Result<Self, ParseScopeErr> {
+        let tokens: Vec<&str> = s
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .collect();
+
+        if let Some(ch) = tokens
+            .iter()
+            .find_map(|token| token.chars().find(|ch| ch.is_whitespace()))
+        {
+            return Err(ParseScopeErr::InvalidCharacter(ch));
+        }
+
+        tokens
+            .into_iter()
+            .map(from_scim_token)
+            .collect::<Vec<_>>()
+            .join(" ")
+            .parse::<Self>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scim_urns_are_normalized() {
+        let scope = Scope::from_scim_scope(
+            "urn:ietf:params:scim:schemas:core:2.0:User, urn:ietf:params:scim:api:messages:2.0:BulkRequest",
+        )
+        .unwrap();
+
+        assert_eq!(
+            scope,
+            "schemas:core:2.0:User api:messages:2.0:BulkRequest"
+                .parse::<Scope>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn scim_tokens_cannot_contain_whitespace() {
+        assert_eq!(
+            Scope::from_scim_scope("users.read, users write"),
+            Err(ParseScopeErr::InvalidCharacter(' '))
+        );
+        assert_eq!(
+            Scope::from_scim_scope("users.read,users\twrite"),
+            Err(ParseScopeErr::InvalidCharacter('\t'))
+        );
+        assert_eq!(
+            Scope::from_scim_scope(" users.read , users.write "),
+            Ok("users.read users.write".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn scim_scope_round_trips() {
+        let scim = "urn:ietf:params:scim:schemas:core:2.0:Group,urn:ietf:params:scim:schemas:core:2.0:User,users.read,!urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+        let scope = Scope::from_scim_scope(scim).unwrap();
+
+        assert!(scope
+            .denied_tokens
+            .contains("schemas:extension:enterprise:2.0:User"));
+        assert_eq!(scope.to_scim_scope(), scim);
+        assert_eq!(
+            Scope::from_scim_scope(&scope.to_scim_scope()).unwrap(),
+            scope
+        );
+    }
+
+    #[test]
+    fn invalid_scim_scope_is_rejected() {
+        assert!(Scope::from_scim_scope("urn:ietf:params:scim:schemas:\"core\"").is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! Scope extraction from environment variables.
+//!
+//! A deployment can grant each token with its own variable: with the `APP_SCOPE_` prefix,
+//! `APP_SCOPE_READ_USER=true` allows `read:user` and `APP_SCOPE_GUEST=false` denies `guest`.
+
+use std::env;
+use std::fmt;
+
+use super::{ascii_validator, ParseScopeErr, Scope};
+
+#[derive(Debug, PartialEq)]
+pub enum ScopeFromEnvError {
+    /// The value of the variable is neither `true`, `false` nor `!`.
+    InvalidValue {
+        name: String,
+        value: String,
+    },
+    /// The name of the variable is the prefix alone, it doesn't give a token.
+    EmptyToken {
+        name: String,
+    },
+    ParseScopeError(ParseScopeErr),
+}
+
+impl From<ParseScopeErr> for ScopeFromEnvError {
+    fn from(error: ParseScopeErr) -> Self {
+        Self::ParseScopeError(error)
+    }
+}
+
+impl fmt::Display for ScopeFromEnvError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::InvalidValue { name, value } => write!(
+                fmt,
+                "Scope variable {name} must be `true`, `false` or `!`, found: {value}"
+            ),
+            Self::EmptyToken { name } => write!(fmt, "Scope variable {name} has no token"),
+            Self::ParseScopeError(error) => error.fmt(fmt),
+        }
+    }
+}
+
+impl std::error::Error for ScopeFromEnvError {}
+
+impl Scope {
+    /// Build a scope from the environment variables whose name starts with `prefix`.
+    ///
+    /// The token is the rest of the name, lowercased, with `_` replaced by the `:` hierarchy
+    /// separator. A `true` value allows the token, a `false` or `!` value denies it. Variables
+    /// whose name isn't valid unicode are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a value is invalid, if a name is the prefix alone or if a token contains an
+    /// invalid character. Tokens can't start with `!`, the denial is given by the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// std::env::set_var("DOC_SCOPE_READ_USER", "true");
+    /// std::env::set_var("DOC_SCOPE_GUEST", "false");
+    ///
+    /// assert_eq!(
+    ///     Scope::from_env_prefixed("DOC_SCOPE_").unwrap(),
+    ///     "read:user !guest".parse().unwrap()
+    /// );
+    /// ```
+    pub fn from_env_prefixed(prefix: &str) -> Result<Self, ScopeFromEnvError> {
+        let mut tokens = vec![];
+
+        for (name, value) in env::vars_os() {
+            let token = match name.to_str().and_then(|name| name.strip_prefix(prefix)) {
+                Some(token) => token.to_lowercase().replace('_', ":"),
+                None => continue,
+            };
+            let value = value.to_string_lossy();
+
+            ascii_validator::validate_scope_string(&token)?;
+            if token.is_empty() {
+                return Err(ScopeFromEnvError::EmptyToken {
+                    name: name.to_string_lossy().into_owned(),
+                });
+            }
+            if let Some(ch) = token.chars().find(|&ch| ch == ' ' || ch == '!') {
+                return Err(ParseScopeErr::InvalidCharacter(ch).into());
+            }
+
+            if value.eq_ignore_ascii_case("true") {
+                tokens.push(token);
+            } else if value.eq_ignore_ascii_case("false") || value == "!" {
+                tokens.push(format!("!{token}"));
+            } else {
+                return Err(ScopeFromEnvError::InvalidValue {
+                    name: name.to_string_lossy().into_owned(),
+                    value: value.into_owned(),
+                });
+            }
+        }
+
+        Ok(tokens.join(" ").parse::<Self>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// The environment is shared by the whole process: these tests are serialized, and each one
+    /// uses its own prefix.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_vars<T>(vars: &[(&str, &str)], check: impl FnOnce() -> T) -> T {
+        let _lock = ENV_LOCK
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        for (name, value) in vars {
+            env::set_var(name, value);
+        }
+        let result = check();
+        for (name, _) in vars {
+            env::remove_var(name);
+        }
+
+        result
+    }
+
+    #[test]
+    fn prefixed_variables_grant_and_deny_tokens() {
+        let vars = [
+            ("AUTHORIZED_TEST_SCOPE_ADMIN", "true"),
+            ("AUTHORIZED_TEST_SCOPE_READ_USER", "TRUE"),
+            ("AUTHORIZED_TEST_SCOPE_GUEST", "false"),
+            ("AUTHORIZED_TEST_SCOPE_BILLING", "!"),
+            ("AUTHORIZED_TEST_OTHER", "true"),
+        ];
+
+        with_vars(&vars, || {
+            assert_eq!(
+                Scope::from_env_prefixed("AUTHORIZED_TEST_SCOPE_").unwrap(),
+                "admin read:user !guest !billing".parse().unwrap()
+            );
+            assert_eq!(
+                Scope::from_env_prefixed("AUTHORIZED_TEST_MISSING_").unwrap(),
+                Scope::from_tokens(&[], &[])
+            );
+        });
+    }
+
+    #[test]
+    fn invalid_variables_are_rejected() {
+        with_vars(&[("AUTHORIZED_TEST_INVALID_ADMIN", "yes")], || {
+            assert_eq!(
+                Scope::from_env_prefixed("AUTHORIZED_TEST_INVALID_"),
+                Err(ScopeFromEnvError::InvalidValue {
+                    name: "AUTHORIZED_TEST_INVALID_ADMIN".to_string(),
+                    value: "yes".to_string(),
+                })
+            );
+        });
+
+        with_vars(&[("AUTHORIZED_TEST_CHARS_READ\"USER", "true")], || {
+            assert_eq!(
+                Scope::from_env_prefixed("AUTHORIZED_TEST_CHARS_"),
+                Err(ScopeFromEnvError::ParseScopeError(
+                    ParseScopeErr::InvalidCharacter('"')
+                ))
+            );
+        });
+    }
+
+    #[test]
+    fn empty_and_denied_tokens_are_rejected() {
+        with_vars(&[("AUTHORIZED_TEST_EMPTY_", "true")], || {
+            assert_eq!(
+                Scope::from_env_prefixed("AUTHORIZED_TEST_EMPTY_"),
+                Err(ScopeFromEnvError::EmptyToken {
+                    name: "AUTHORIZED_TEST_EMPTY_".to_string(),
+                })
+            );
+        });
+
+        with_vars(&[("AUTHORIZED_TEST_BANG_!", "false")], || {
+            assert_eq!(
+                Scope::from_env_prefixed("AUTHORIZED_TEST_BANG_"),
+                Err(ScopeFromEnvError::ParseScopeError(
+                    ParseScopeErr::InvalidCharacter('!')
+                ))
+            );
+        });
+
+        with_vars(&[("AUTHORIZED_TEST_BANG_!ADMIN", "true")], || {
+            assert_eq!(
+                Scope::from_env_prefixed("AUTHORIZED_TEST_BANG_"),
+                Err(ScopeFromEnvError::ParseScopeError(
+                    ParseScopeErr::InvalidCharacter('!')
+                ))
+            );
+        });
+    }
+}
@@ -0,0 +1,128 @@
+//! Hierarchical scopes.
+//!
+//! In a hierarchical scope the colon (`:`) separates a token from its parent: holding `admin`
+//! implies holding `admin:read`, `admin:write` and `admin:read:logs`. Denied tokens deny their
+//! children too.
+
+use std::str;
+
+use super::{ParseScopeErr, Scope};
+
+const SEPARATOR: char = ':';
+
+/// A [`Scope`](struct.Scope.html) comparing its tokens with parent-implies-child semantics.
+///
+/// # Examples
+/// ```
+/// use authorized::scope::HierarchicalScope;
+///
+/// let admin = "admin".parse::<HierarchicalScope>().unwrap();
+/// let admin_read = "admin:read".parse::<HierarchicalScope>().unwrap();
+///
+/// assert!(admin_read.allow_access(&admin));
+/// assert!(!admin.allow_access(&admin_read));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HierarchicalScope(Scope);
+
+impl HierarchicalScope {
+    /// Checks if `held` is `required` or one of its parents.
+    fn implies(held: &str, required: &str) -> bool {
+        required
+            .strip_prefix(held)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(SEPARATOR))
+    }
+
+    /// Determines if a resource protected by this scope should allow access to a token with the
+    /// grant on the right side, a token being implied by each of its parents.
+    #[must_use]
+    pub fn allow_access(&self, rhs: &Self) -> bool {
+        let (resource, authorizer) = (&self.0, &rhs.0);
+
+        let denied_by_resource = resource.denied_tokens.iter().any(|denied| {
+            authorizer
+                .allowed_tokens
+                .iter()
+                .any(|held| Self::implies(denied, held))
+        });
+        let denied_by_authorizer = authorizer.denied_tokens.iter().any(|denied| {
+            resource
+                .allowed_tokens
+                .iter()
+                .any(|required| Self::implies(denied, required))
+        });
+
+        !denied_by_resource
+            && !denied_by_authorizer
+            && resource.allowed_tokens.iter().all(|required| {
+                authorizer
+                    .allowed_tokens
+                    .iter()
+                    .any(|held| Self::implies(held, required))
+            })
+    }
+
+    /// Determines if this scope has enough privileges to access some resource requiring the scope
+    /// on the right side.
+    #[must_use]
+    pub fn priviledged_to(&self, rhs: &Self) -> bool {
+        rhs.allow_access(self)
+    }
+
+    #[must_use]
+    pub fn as_scope(&self) -> &Scope {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn into_scope(self) -> Scope {
+        self.0
+    }
+}
+
+impl From<Scope> for HierarchicalScope {
+    fn from(scope: Scope) -> Self {
+        Self(scope)
+    }
+}
+
+impl str::FromStr for HierarchicalScope {
+    type Err = ParseScopeErr;
+
+    fn from_str(string: &str) -> Result<Self, ParseScopeErr> {
+        string.parse::<Scope>().map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> HierarchicalScope {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parents_imply_children() {
+        assert!(parse("admin").priviledged_to(&parse("admin:read")));
+        assert!(parse("admin:read").allow_access(&parse("admin")));
+        assert!(parse("admin:read:logs").allow_access(&parse("admin")));
+        assert!(parse("admin:read user").allow_access(&parse("admin user")));
+
+        assert!(!parse("admin").allow_access(&parse("admin:read")));
+        assert!(!parse("administrator").allow_access(&parse("admin")));
+        assert!(!parse("admin:read user").allow_access(&parse("admin")));
+
+        // Without colons, the semantics are the same as `Scope`.
+        assert!(parse("user").allow_access(&parse("user read:user")));
+        assert!(!parse("admin").allow_access(&parse("user")));
+    }
+
+    #[test]
+    fn denied_parents_deny_children() {
+        assert!(!parse("!admin").allow_access(&parse("admin:read")));
+        assert!(parse("!admin:write").allow_access(&parse("admin:read")));
+        assert!(!parse("admin:write").allow_access(&parse("admin !admin:write")));
+        assert!(parse("admin:read").allow_access(&parse("admin !admin:write")));
+    }
+}
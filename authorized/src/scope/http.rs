@@ -0,0 +1,256 @@
+//! Conversion between scopes and `Authorization: Bearer <scope>` header values, and with the
+//! `with_http` feature `WWW-Authenticate: Bearer` challenges.
+
+#[cfg(feature = "with_http")]
+use super::ParseScopeErr;
+use super::Scope;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Strips the `Bearer ` scheme of `header`, compared case-insensitively as required by RFC 7235.
+fn strip_bearer_scheme(header: &str) -> Option<&str> {
+    let scheme = header.get(..BEARER_PREFIX.len())?;
+
+    if scheme.eq_ignore_ascii_case(BEARER_PREFIX) {
+        Some(&header[BEARER_PREFIX.len()..])
+    } else {
+        None
+    }
+}
+
+/// Splits a `WWW-Authenticate` value into its challenges: a scheme followed by auth-params,
+/// `name=value` or `name="quoted value"` separated by commas. A token that isn't followed by `=`
+/// starts the next challenge. The values are unquoted.
+#[cfg(feature = "with_http")]
+fn challenges(header: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut challenges: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    let mut chars = header.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+
+        let mut token = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ',' && *c != '=') {
+            token.push(c);
+        }
+        if token.is_empty() {
+            break;
+        }
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next_if_eq(&'=').is_none() {
+            challenges.push((token, Vec::new()));
+            continue;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => value.extend(chars.next()),
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                value.push(c);
+            }
+            value.truncate(value.trim_end().len());
+        }
+
+        if let Some((_, params)) = challenges.last_mut() {
+            params.push((token, value));
+        }
+    }
+
+    challenges
+}
+
+impl Scope {
+    /// Parses the scope of an `Authorization: Bearer <scope>` header value.
+    ///
+    /// Returns `None` if the value doesn't start with `Bearer `, whatever its case, or if the
+    /// scope is invalid.
+    #[must_use]
+    pub fn from_bearer(header: &str) -> Option<Self> {
+        strip_bearer_scheme(header).and_then(|scope| scope.parse::<Self>().ok())
+    }
+
+    /// Formats the scope as an `Authorization` header value: `Bearer ` followed by the
+    /// canonical scope string. An empty scope gives `Bearer `.
+    #[must_use]
+    pub fn to_bearer_header(&self) -> String {
+        format!("{BEARER_PREFIX}{self}")
+    }
+
+    /// Parses the scopes of the `scope` parameters of the `WWW-Authenticate: Bearer` challenges,
+    /// as returned by `OAuth2` servers when a token is insufficient (RFC 6750).
+    ///
+    /// Every challenge of the value is scanned, and the `Bearer` scheme is compared
+    /// case-insensitively. Returns an empty `Vec` if the value has no `Bearer` challenge or no
+    /// `scope` parameter.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a `scope` parameter isn't a valid scope.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    ///
+    /// let header = r#"Bearer realm="example", error="insufficient_scope", scope="read:user write:user""#;
+    ///
+    /// assert_eq!(
+    ///     Scope::from_www_authenticate(header).unwrap(),
+    ///     vec!["read:user write:user".parse::<Scope>().unwrap()]
+    /// );
+    /// ```
+    #[cfg(feature = "with_http")]
+    pub fn from_www_authenticate(header: &str) -> Result<Vec<Self>, ParseScopeErr> {
+        challenges(header)
+            .into_iter()
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(BEARER_PREFIX.trim_end()))
+            .flat_map(|(_, params)| params)
+            .filter(|(name, _)| name.eq_ignore_ascii_case("scope"))
+            .map(|(_, scope)| scope.parse())
+            .collect()
+    }
+
+    /// Formats a `WWW-Authenticate` header value challenging the client for this scope:
+    /// `Bearer realm="<realm>", scope="<scope>"`.
+    #[cfg(feature = "with_http")]
+    #[must_use]
+    pub fn to_www_authenticate_challenge(&self, realm: &str) -> String {
+        let realm = realm.replace('\\', "\\\\").replace('"', "\\\"");
+
+        format!("{BEARER_PREFIX}realm=\"{realm}\", scope=\"{self}\"")
+    }
+
+    /// Same as [`to_bearer_header`](#method.to_bearer_header) but produces an
+    /// `http::HeaderValue`.
+    ///
+    /// # Panics
+    ///
+    /// Never panics: scope characters are valid header value characters.
+    #[cfg(feature = "with_http")]
+    #[must_use]
+    pub fn to_authorization_header(&self) -> ::http::HeaderValue {
+        ::http::HeaderValue::from_str(&self.to_bearer_header())
+            .expect("scope characters are valid header value characters")
+    }
+}
+
+/// Produces the `Authorization` header value of `scope`, e.g. for `reqwest` or `hyper` clients.
+#[cfg(feature = "with_http")]
+#[must_use]
+pub fn scope_as_bearer(scope: &Scope) -> ::http::header::HeaderValue {
+    scope.to_authorization_header()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bearer_headers_round_trip() {
+        let scope = "user admin !guest".parse::<Scope>().unwrap();
+
+        assert_eq!(scope.to_bearer_header(), "Bearer admin user !guest");
+        assert_eq!(Scope::from_bearer(&scope.to_bearer_header()), Some(scope));
+
+        let empty = "".parse::<Scope>().unwrap();
+        assert_eq!(empty.to_bearer_header(), "Bearer ");
+        assert_eq!(Scope::from_bearer("Bearer "), Some(empty));
+
+        assert_eq!(
+            Scope::from_bearer("bearer admin"),
+            Some("admin".parse().unwrap())
+        );
+        assert_eq!(
+            Scope::from_bearer("BEARER admin"),
+            Some("admin".parse().unwrap())
+        );
+
+        assert_eq!(Scope::from_bearer("Basic dXNlcg=="), None);
+        assert_eq!(Scope::from_bearer("Bear"), None);
+        assert_eq!(Scope::from_bearer("Bearer \"admin\""), None);
+    }
+
+    #[cfg(feature = "with_http")]
+    #[test]
+    fn authorization_header_values_can_be_produced() {
+        let scope = "read:user".parse::<Scope>().unwrap();
+
+        assert_eq!(scope_as_bearer(&scope), "Bearer read:user");
+        assert_eq!(
+            Scope::from_bearer(scope.to_authorization_header().to_str().unwrap()),
+            Some(scope)
+        );
+    }
+
+    #[cfg(feature = "with_http")]
+    #[test]
+    fn www_authenticate_challenges_can_be_parsed() {
+        let parse = |s: &str| s.parse::<Scope>().unwrap();
+
+        // Examples of RFC 6750, section 3.
+        assert_eq!(
+            Scope::from_www_authenticate(r#"Bearer realm="example""#),
+            Ok(vec![])
+        );
+        assert_eq!(
+            Scope::from_www_authenticate(
+                r#"Bearer realm="example", error="invalid_token", error_description="The access token expired""#
+            ),
+            Ok(vec![])
+        );
+        assert_eq!(
+            Scope::from_www_authenticate(
+                r#"Bearer realm="example", error="insufficient_scope", scope="read:user write:user""#
+            ),
+            Ok(vec![parse("read:user write:user")])
+        );
+
+        assert_eq!(
+            Scope::from_www_authenticate(r#"Bearer scope="admin", realm="a, \"b\"", scope=user"#),
+            Ok(vec![parse("admin"), parse("user")])
+        );
+        assert_eq!(
+            Scope::from_www_authenticate(r#"Basic realm="example", scope="admin""#),
+            Ok(vec![])
+        );
+        assert_eq!(
+            Scope::from_www_authenticate(r#"bearer scope="admin""#),
+            Ok(vec![parse("admin")])
+        );
+        assert_eq!(
+            Scope::from_www_authenticate(
+                r#"Basic realm="x", Bearer scope="a", Negotiate dG9rZW4=, BEARER scope=b"#
+            ),
+            Ok(vec![parse("a"), parse("b")])
+        );
+        assert_eq!(
+            Scope::from_www_authenticate(r#"Bearer, Basic scope="admin""#),
+            Ok(vec![])
+        );
+        assert_eq!(
+            Scope::from_www_authenticate(r#"Bearer scope="read:\\user""#),
+            Err(ParseScopeErr::InvalidCharacter('\\'))
+        );
+    }
+
+    #[cfg(feature = "with_http")]
+    #[test]
+    fn www_authenticate_challenges_round_trip() {
+        let scope = "read:user !admin".parse::<Scope>().unwrap();
+        let challenge = scope.to_www_authenticate_challenge("my \"api\"");
+
+        assert_eq!(
+            challenge,
+            r#"Bearer realm="my \"api\"", scope="read:user !admin""#
+        );
+        assert_eq!(Scope::from_www_authenticate(&challenge), Ok(vec![scope]));
+    }
+}
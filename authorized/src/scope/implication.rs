@@ -0,0 +1,136 @@
+//! Scope implications.
+//!
+//! Some systems define roles as tokens implying other tokens: holding `admin` implies holding
+//! `read:user` and `write:user`. Expanding a scope with its implications lets these roles be
+//! checked with the usual [`Scope::allow_access`](../struct.Scope.html#method.allow_access).
+
+use std::collections::{HashMap, HashSet};
+
+use super::Scope;
+
+/// The maximum length of an implication chain.
+const MAX_DEPTH: usize = 10;
+
+impl Scope {
+    /// Build a scope with the allowed tokens of `self` and every token they imply, recursively.
+    /// Denied tokens are kept as is.
+    ///
+    /// Cyclic implications are expanded once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a chain of implications is longer than 10 levels.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use authorized::prelude::*;
+    ///
+    /// let mut implications = HashMap::new();
+    /// implications.insert("admin".to_string(), vec!["read:user".to_string(), "write:user".to_string()]);
+    /// implications.insert("read:user".to_string(), vec!["read:profile".to_string()]);
+    ///
+    /// let admin = "admin".parse::<Scope>().unwrap();
+    /// assert_eq!(
+    ///     admin.expand_with_implies(&implications),
+    ///     "admin read:user write:user read:profile".parse().unwrap()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn expand_with_implies(&self, implications: &HashMap<String, Vec<String>>) -> Self {
+        let mut allowed_tokens = self.allowed_tokens.clone();
+        let mut level: HashSet<String> = self.allowed_tokens.clone();
+
+        for _ in 0..=MAX_DEPTH {
+            level = level
+                .iter()
+                .filter_map(|token| implications.get(token))
+                .flatten()
+                .filter(|token| !allowed_tokens.contains(*token))
+                .cloned()
+                .collect();
+
+            if level.is_empty() {
+                return Self {
+                    denied_tokens: self.denied_tokens.clone(),
+                    allowed_tokens,
+                };
+            }
+
+            allowed_tokens.extend(level.iter().cloned());
+        }
+
+        panic!(
+            "Scope implications are nested more than {:?} levels deep",
+            MAX_DEPTH
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn implications(pairs: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        pairs
+            .iter()
+            .map(|(token, implied)| {
+                (
+                    (*token).to_string(),
+                    implied.iter().map(ToString::to_string).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn implied_tokens_are_added_recursively() {
+        let implications = implications(&[
+            ("admin", &["read:user", "write:user"]),
+            ("read:user", &["read:profile"]),
+        ]);
+
+        let scope = "admin !guest".parse::<Scope>().unwrap();
+        assert_eq!(
+            scope.expand_with_implies(&implications),
+            "admin read:user write:user read:profile !guest"
+                .parse()
+                .unwrap()
+        );
+
+        let scope = "read:user".parse::<Scope>().unwrap();
+        assert_eq!(
+            scope.expand_with_implies(&implications),
+            "read:user read:profile".parse().unwrap()
+        );
+        assert_eq!(scope.expand_with_implies(&HashMap::new()), scope);
+    }
+
+    #[test]
+    fn cycles_terminate_and_deep_chains_panic() {
+        let cyclic = implications(&[("admin", &["owner"]), ("owner", &["admin"])]);
+        let scope = "admin".parse::<Scope>().unwrap();
+        assert_eq!(
+            scope.expand_with_implies(&cyclic),
+            "admin owner".parse().unwrap()
+        );
+
+        let tokens: Vec<String> = (0..=MAX_DEPTH + 1).map(|i| format!("level{i}")).collect();
+        let deep: HashMap<String, Vec<String>> = tokens
+            .windows(2)
+            .map(|pair| (pair[0].clone(), vec![pair[1].clone()]))
+            .collect();
+        let scope = "level0".parse::<Scope>().unwrap();
+        assert!(std::panic::catch_unwind(|| scope.expand_with_implies(&deep)).is_err());
+
+        let shallow: HashMap<String, Vec<String>> = deep
+            .into_iter()
+            .filter(|(token, _)| token != "level10")
+            .collect();
+        assert_eq!(
+            scope.expand_with_implies(&shallow).allowed_count(),
+            MAX_DEPTH + 1
+        );
+    }
+}
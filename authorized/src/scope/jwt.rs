@@ -0,0 +1,154 @@
+//! Scope extraction from JWT claims.
+//!
+//! Depending on the issuer, the scope of a JWT is either a space delimited string
+//! (`"scope": "read:user write:user"`) or an array of strings
+//! (`"permissions": ["read:user", "write:user"]`). Both formats are supported.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde_json::Value;
+
+use super::{ascii_validator, ParseScopeErr, Scope};
+
+#[derive(Debug)]
+pub enum ScopeFromJwtError {
+    /// The requested claim isn't part of the claim set.
+    MissingClaim(String),
+    /// The claim (or one of its items) is neither a string nor an array of strings.
+    InvalidClaimType(Value),
+    /// An item of an array claim isn't a single scope token, like `"admin read"`.
+    InvalidToken(String),
+    ParseScopeError(ParseScopeErr),
+}
+
+impl From<ParseScopeErr> for ScopeFromJwtError {
+    fn from(error: ParseScopeErr) -> Self {
+        Self::ParseScopeError(error)
+    }
+}
+
+impl fmt::Display for ScopeFromJwtError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::MissingClaim(key) => write!(fmt, "Missing scope claim: {key}"),
+            Self::InvalidClaimType(value) => write!(
+                fmt,
+                "Scope claim must be a string or an array of strings, found: {value}"
+            ),
+            Self::InvalidToken(token) => write!(fmt, "Invalid scope token in claim: {token:?}"),
+            Self::ParseScopeError(error) => error.fmt(fmt),
+        }
+    }
+}
+
+impl Scope {
+    /// Build a scope from the claim named `scope_key` in a JWT claim set.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the claim is missing, if it isn't a string or an array of strings, or if a
+    /// token contains an invalid character.
+    pub fn from_claim_set(
+        claims: &BTreeMap<String, Value>,
+        scope_key: &str,
+    ) -> Result<Self, ScopeFromJwtError> {
+        match claims.get(scope_key) {
+            None => Err(ScopeFromJwtError::MissingClaim(scope_key.to_string())),
+            Some(Value::String(scope)) => Ok(scope.parse::<Self>()?),
+            Some(Value::Array(values)) => Self::from_json_array(values),
+            Some(value) => Err(ScopeFromJwtError::InvalidClaimType(value.clone())),
+        }
+    }
+
+    /// Build a scope from a JSON array of scope strings.
+    ///
+    /// # Errors
+    ///
+    /// Fails if an item isn't a string or isn't a single valid token. An item is never split
+    /// on spaces, `["admin read"]` is rejected.
+    pub fn from_json_array(values: &[Value]) -> Result<Self, ScopeFromJwtError> {
+        let tokens = values
+            .iter()
+            .map(|value| match value {
+                Value::String(token) if ascii_validator::is_valid_scope_token(token) => {
+                    Ok(token.as_str())
+                }
+                Value::String(token) => Err(ScopeFromJwtError::InvalidToken(token.clone())),
+                value => Err(ScopeFromJwtError::InvalidClaimType(value.clone())),
+            })
+            .collect::<Result<Vec<&str>, _>>()?;
+
+        Ok(Self::try_from_iter(tokens)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn claims(value: Value) -> BTreeMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn scope_can_be_read_from_string_or_array_claims() {
+        let claims = claims(json!({
+            "sub": "1234567890",
+            "aud": "api",
+            "scope": "read:user write:user",
+            "permissions": ["read:user", "write:user"],
+        }));
+
+        let expected = "read:user write:user".parse::<Scope>().unwrap();
+        assert_eq!(Scope::from_claim_set(&claims, "scope").unwrap(), expected);
+        assert_eq!(
+            Scope::from_claim_set(&claims, "permissions").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn invalid_claims_are_rejected() {
+        let claims = claims(json!({
+            "scope": "read:\"user\"",
+            "roles": ["admin", 42],
+            "exp": 1_516_239_022,
+        }));
+
+        assert!(matches!(
+            Scope::from_claim_set(&claims, "missing"),
+            Err(ScopeFromJwtError::MissingClaim(_))
+        ));
+        assert!(matches!(
+            Scope::from_claim_set(&claims, "scope"),
+            Err(ScopeFromJwtError::ParseScopeError(_))
+        ));
+        assert!(matches!(
+            Scope::from_claim_set(&claims, "roles"),
+            Err(ScopeFromJwtError::InvalidClaimType(_))
+        ));
+        assert!(matches!(
+            Scope::from_claim_set(&claims, "exp"),
+            Err(ScopeFromJwtError::InvalidClaimType(_))
+        ));
+    }
+
+    #[test]
+    fn array_items_must_be_single_tokens() {
+        let claims = claims(json!({
+            "spaced": ["admin read", "write"],
+            "empty": ["admin", ""],
+            "invalid": ["read:\"user\""],
+        }));
+
+        for key in ["spaced", "empty", "invalid"] {
+            assert!(matches!(
+                Scope::from_claim_set(&claims, key),
+                Err(ScopeFromJwtError::InvalidToken(_))
+            ));
+        }
+    }
+}
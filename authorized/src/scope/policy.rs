@@ -0,0 +1,210 @@
+//! Policy driven scope modification.
+//!
+//! An [`AuthorizationPolicy`](struct.AuthorizationPolicy.html) centralizes the rules deriving
+//! tokens from other tokens: "holding `user` always grants `read:profile`", "holding `banned`
+//! denies `write:*`". Applying the policy to the scope of a request before authorizing keeps
+//! these rules out of the [`Authorizable`](../../trait.Authorizable.html) implementations.
+//!
+//! With the `with_serde` feature a policy can be loaded from a configuration file.
+
+use super::ascii_validator::is_valid_scope_token;
+use super::Scope;
+
+/// Rules transforming a scope, applied by
+/// [`Scope::apply_policy`](../struct.Scope.html#method.apply_policy).
+///
+/// Each rule is a trigger token and the tokens to add to (or deny in) scopes holding it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "with_serde",
+    derive(::serde::Deserialize, ::serde::Serialize)
+)]
+pub struct AuthorizationPolicy {
+    #[cfg_attr(feature = "with_serde", serde(default))]
+    pub add_if_contains: Vec<(String, Vec<String>)>,
+    #[cfg_attr(feature = "with_serde", serde(default))]
+    pub deny_if_contains: Vec<(String, Vec<String>)>,
+}
+
+impl AuthorizationPolicy {
+    #[must_use]
+    pub fn builder() -> AuthorizationPolicyBuilder {
+        AuthorizationPolicyBuilder {
+            policy: Self::default(),
+        }
+    }
+}
+
+/// Builder of [`AuthorizationPolicy`](struct.AuthorizationPolicy.html).
+#[derive(Debug)]
+pub struct AuthorizationPolicyBuilder {
+    policy: AuthorizationPolicy,
+}
+
+impl AuthorizationPolicyBuilder {
+    fn tokens(trigger: &str, tokens: &[&str]) -> (String, Vec<String>) {
+        for token in std::iter::once(&trigger).chain(tokens) {
+            assert!(
+                is_valid_scope_token(token) && !token.starts_with('!'),
+                "Invalid policy token: {:?}",
+                token
+            );
+        }
+
+        (
+            trigger.to_string(),
+            tokens.iter().map(ToString::to_string).collect(),
+        )
+    }
+
+    /// Add `add_tokens` to the allowed tokens of scopes holding `trigger`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a token isn't a valid allowed token, see
+    /// [`is_valid_scope_token`](../ascii_validator/fn.is_valid_scope_token.html).
+    #[must_use]
+    pub fn add_rule(mut self, trigger: &str, add_tokens: &[&str]) -> Self {
+        self.policy
+            .add_if_contains
+            .push(Self::tokens(trigger, add_tokens));
+        self
+    }
+
+    /// Deny `deny_tokens` in scopes holding `trigger`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a token isn't a valid allowed token, see
+    /// [`is_valid_scope_token`](../ascii_validator/fn.is_valid_scope_token.html). Tokens are
+    /// given without their `!` prefix.
+    #[must_use]
+    pub fn deny_rule(mut self, trigger: &str, deny_tokens: &[&str]) -> Self {
+        self.policy
+            .deny_if_contains
+            .push(Self::tokens(trigger, deny_tokens));
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> AuthorizationPolicy {
+        self.policy
+    }
+}
+
+impl Scope {
+    /// Build a scope with `self` transformed by the rules of `policy`.
+    ///
+    /// The `add_if_contains` rules are applied in order, then the `deny_if_contains` rules. A
+    /// rule matches when its trigger is an allowed token of the scope built so far, so tokens
+    /// added by a rule can trigger the following rules.
+    ///
+    /// The tokens of a deserialized policy aren't validated.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::prelude::*;
+    /// use authorized::scope::AuthorizationPolicy;
+    ///
+    /// let policy = AuthorizationPolicy::builder()
+    ///     .add_rule("user", &["read:profile"])
+    ///     .deny_rule("banned", &["write:*"])
+    ///     .build();
+    ///
+    /// let scope = "user banned".parse::<Scope>().unwrap();
+    /// assert_eq!(
+    ///     scope.apply_policy(&policy),
+    ///     "user banned read:profile !write:*".parse().unwrap()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn apply_policy(&self, policy: &AuthorizationPolicy) -> Self {
+        let mut scope = self.clone();
+
+        for (trigger, tokens) in &policy.add_if_contains {
+            if scope.contains_token(trigger) {
+                scope.allowed_tokens.extend(tokens.iter().cloned());
+            }
+        }
+
+        for (trigger, tokens) in &policy.deny_if_contains {
+            if scope.contains_token(trigger) {
+                scope.denied_tokens.extend(tokens.iter().cloned());
+            }
+        }
+
+        scope
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(scope: &str) -> Scope {
+        scope.parse().unwrap()
+    }
+
+    fn policy() -> AuthorizationPolicy {
+        AuthorizationPolicy::builder()
+            .add_rule("user", &["read:profile"])
+            .add_rule("admin", &["user", "write:user"])
+            .add_rule("read:profile", &["read:avatar"])
+            .deny_rule("banned", &["write:*"])
+            .build()
+    }
+
+    #[test]
+    fn matching_rules_are_applied_in_order() {
+        let policy = policy();
+
+        assert_eq!(scope("").apply_policy(&policy), scope(""));
+        assert_eq!(
+            scope("guest !admin").apply_policy(&policy),
+            scope("guest !admin")
+        );
+        assert_eq!(
+            scope("user").apply_policy(&policy),
+            scope("user read:profile read:avatar")
+        );
+        assert_eq!(
+            scope("user banned").apply_policy(&policy),
+            scope("user banned read:profile read:avatar !write:*")
+        );
+
+        // `admin` adds `user` after the `user` rule ran, it isn't applied again.
+        let admin = scope("admin banned").apply_policy(&policy);
+        assert_eq!(admin, scope("admin banned user write:user !write:*"));
+        assert!(admin.denies_token("write:user"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid policy token")]
+    fn builder_rejects_invalid_tokens() {
+        let _ = AuthorizationPolicy::builder().add_rule("user", &["!admin"]);
+    }
+
+    #[cfg(feature = "with_serde")]
+    #[test]
+    fn policies_can_be_deserialized() {
+        let policy: AuthorizationPolicy = serde_json::from_str(
+            r#"{
+                "add_if_contains": [["user", ["read:profile"]]],
+                "deny_if_contains": [["banned", ["write:*"]]]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            policy,
+            AuthorizationPolicy::builder()
+                .add_rule("user", &["read:profile"])
+                .deny_rule("banned", &["write:*"])
+                .build()
+        );
+        assert_eq!(
+            serde_json::from_str::<AuthorizationPolicy>("{}").unwrap(),
+            AuthorizationPolicy::default()
+        );
+    }
+}
@@ -1,6 +1,17 @@
+use std::collections::BTreeMap;
+
+use ::serde::de::{self, Deserialize, Deserializer};
+use ::serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::result::AuthorizationStatus;
+use crate::scope::Scope;
 use crate::Authorized;
 use crate::AuthorizedResult;
+use crate::ScopeRequirements;
 
+/// Only the inner value is serialized. Nested results are serialized the same way, so the
+/// output of an authorized `Vec<T>`, a `Vec<AuthorizedResult<T::Authorized>>`, is serialized as
+/// an array of the authorized items, without requiring `T: Clone`.
 #[cfg(feature = "with_serde")]
 impl<T: ::serde::ser::Serialize + Authorized> ::serde::ser::Serialize for AuthorizedResult<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -10,3 +21,517 @@ impl<T: ::serde::ser::Serialize + Authorized> ::serde::ser::Serialize for Author
         self.inner.serialize(serializer)
     }
 }
+
+/// The metadata of the `{ "data": inner, "meta": { .. } }` envelope format, written by
+/// [`SerializationMode::WithStatus`](enum.SerializationMode.html#variant.WithStatus).
+#[derive(::serde::Serialize)]
+struct MetaRef<'a> {
+    input_scope: &'a Scope,
+    status: &'a AuthorizationStatus,
+    unauthorized_fields: &'a [String],
+}
+
+#[derive(::serde::Serialize)]
+struct EnvelopeRef<'a, T> {
+    data: &'a T,
+    meta: MetaRef<'a>,
+}
+
+#[derive(::serde::Deserialize)]
+struct Meta {
+    #[serde(default)]
+    input_scope: Option<String>,
+    #[serde(default)]
+    status: Option<AuthorizationStatus>,
+    #[serde(default)]
+    unauthorized_fields: Vec<String>,
+}
+
+#[derive(::serde::Deserialize)]
+#[serde(untagged)]
+enum Repr<T> {
+    Envelope { data: T, meta: Meta },
+    Inner(T),
+}
+
+/// Deserializes either the `{ "data": inner, "meta": { .. } }` envelope format or the inner
+/// value alone. The inner value alone produces an `Authorized` result with an empty scope and
+/// no unauthorized fields.
+impl<'de, T: Deserialize<'de> + Authorized> Deserialize<'de> for AuthorizedResult<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (inner, meta) = match Repr::deserialize(deserializer)? {
+            Repr::Envelope { data, meta } => (data, Some(meta)),
+            Repr::Inner(inner) => (inner, None),
+        };
+
+        let Meta {
+            input_scope,
+            status,
+            unauthorized_fields,
+        } = meta.unwrap_or(Meta {
+            input_scope: None,
+            status: None,
+            unauthorized_fields: vec![],
+        });
+
+        let input_scope = match input_scope {
+            Some(scope) => scope.parse::<Scope>().map_err(de::Error::custom)?,
+            None => Scope::default(),
+        };
+
+        Ok(Self {
+            input_scope,
+            inner,
+            status: status.unwrap_or(AuthorizationStatus::Authorized),
+            unauthorized_fields,
+        })
+    }
+}
+
+/// How a [`SerializingAuthorizor`](struct.SerializingAuthorizor.html) serializes its
+/// `AuthorizedResult`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SerializationMode {
+    /// Only serialize `inner`, like the `Serialize` impl of `AuthorizedResult`.
+    #[default]
+    InnerOnly,
+    /// Serialize the `{ "data": inner, "meta": { input_scope, status, unauthorized_fields } }`
+    /// envelope format, which the `Deserialize` impl of `AuthorizedResult` reads back.
+    WithStatus,
+    /// Serialize `null` when the status is `UnAuthorized`, `inner` otherwise.
+    NullIfUnauthorized,
+}
+
+/// Serializes an `AuthorizedResult` according to a
+/// [`SerializationMode`](enum.SerializationMode.html).
+///
+/// Created by [`AuthorizedResult::serialize_mode`](struct.AuthorizedResult.html#method.serialize_mode).
+#[derive(Debug)]
+pub struct SerializingAuthorizor<'a, T> {
+    result: &'a AuthorizedResult<T>,
+    mode: SerializationMode,
+}
+
+impl<T> AuthorizedResult<T> {
+    /// Wraps the result to serialize it according to `mode`.
+    ///
+    /// # Examples
+    /// ```
+    /// use authorized::SerializationMode;
+    /// # use authorized::prelude::*;
+    /// # let result = AuthorizedResult {
+    /// #     input_scope: "guest".parse::<Scope>().unwrap(),
+    /// #     inner: 42,
+    /// #     status: AuthorizationStatus::UnAuthorized,
+    /// #     unauthorized_fields: vec![],
+    /// # };
+    ///
+    /// let json = serde_json::to_string(&result.serialize_mode(SerializationMode::NullIfUnauthorized));
+    /// assert_eq!(json.unwrap(), "null");
+    /// ```
+    #[must_use]
+    pub fn serialize_mode(&self, mode: SerializationMode) -> SerializingAuthorizor<'_, T> {
+        SerializingAuthorizor { result: self, mode }
+    }
+}
+
+impl<T: Serialize> Serialize for SerializingAuthorizor<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.mode {
+            SerializationMode::InnerOnly => self.result.inner.serialize(serializer),
+            SerializationMode::WithStatus => EnvelopeRef {
+                data: &self.result.inner,
+                meta: MetaRef {
+                    input_scope: &self.result.input_scope,
+                    status: &self.result.status,
+                    unauthorized_fields: &self.result.unauthorized_fields,
+                },
+            }
+            .serialize(serializer),
+            SerializationMode::NullIfUnauthorized => match self.result.status {
+                AuthorizationStatus::Authorized => self.result.inner.serialize(serializer),
+                AuthorizationStatus::UnAuthorized => serializer.serialize_none(),
+            },
+        }
+    }
+}
+
+/// A scope is serialized as its canonical string, see
+/// [`Scope`'s `Display`](scope/struct.Scope.html#impl-Display-for-Scope).
+impl Serialize for Scope {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Serializes a scope as its canonical string, to be used with
+/// `#[serde(serialize_with = "authorized::scope_as_string")]`.
+///
+/// # Errors
+///
+/// Returns the error of the serializer.
+///
+/// # Examples
+/// ```
+/// use authorized::prelude::*;
+///
+/// #[derive(serde::Serialize)]
+/// struct Token {
+///     #[serde(serialize_with = "authorized::scope_as_string")]
+///     scope: Scope,
+/// }
+///
+/// let token = Token { scope: "user admin".parse().unwrap() };
+/// assert_eq!(serde_json::to_string(&token).unwrap(), r#"{"scope":"admin user"}"#);
+/// ```
+pub fn scope_as_string<S: Serializer>(scope: &Scope, serializer: S) -> Result<S::Ok, S::Error> {
+    scope.serialize(serializer)
+}
+
+/// Checks if `field_name` is one of `unauthorized_fields`.
+///
+/// `field` is only there to infer the field being checked, its value is ignored.
+#[must_use]
+pub fn is_field_unauthorized<T>(
+    _field: &T,
+    unauthorized_fields: &[String],
+    field_name: &str,
+) -> bool {
+    unauthorized_fields.iter().any(|field| field == field_name)
+}
+
+/// A field value of a response DTO, `None` when the field is unauthorized.
+///
+/// `MaybeAuthorized(None)` is serialized as `null`, add
+/// `#[serde(skip_serializing_if = "MaybeAuthorized::is_unauthorized")]` to skip it instead.
+/// `MaybeAuthorized(Some(value))` is serialized as `value`.
+///
+/// # Examples
+/// ```
+/// use authorized::MaybeAuthorized;
+///
+/// #[derive(serde::Serialize)]
+/// struct UserResponse {
+///     name: MaybeAuthorized<String>,
+///     #[serde(skip_serializing_if = "MaybeAuthorized::is_unauthorized")]
+///     email: MaybeAuthorized<String>,
+/// }
+///
+/// let unauthorized_fields = vec!["name".to_string(), "email".to_string()];
+/// let response = UserResponse {
+///     name: MaybeAuthorized::new("name".into(), &unauthorized_fields, "name"),
+///     email: MaybeAuthorized::new("email".into(), &unauthorized_fields, "email"),
+/// };
+///
+/// assert_eq!(serde_json::to_string(&response).unwrap(), r#"{"name":null}"#);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MaybeAuthorized<T>(pub Option<T>);
+
+impl<T> MaybeAuthorized<T> {
+    /// Keeps `value` unless `field_name` is one of `unauthorized_fields`.
+    #[must_use]
+    pub fn new(value: T, unauthorized_fields: &[String], field_name: &str) -> Self {
+        if is_field_unauthorized(&value, unauthorized_fields, field_name) {
+            Self(None)
+        } else {
+            Self(Some(value))
+        }
+    }
+
+    #[must_use]
+    pub const fn is_unauthorized(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl<T> From<Option<T>> for MaybeAuthorized<T> {
+    fn from(value: Option<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Serialize> Serialize for MaybeAuthorized<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.0 {
+            Some(value) => value.serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Field scopes are sorted by field name.
+impl Serialize for ScopeRequirements {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let field_scopes: BTreeMap<&String, &Scope> = self.field_scopes.iter().collect();
+
+        let mut state = serializer.serialize_struct("ScopeRequirements", 2)?;
+        state.serialize_field("global_scope", &self.global_scope)?;
+        state.serialize_field("field_scopes", &field_scopes)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scope::Scope;
+    use crate::UnAuthorizedFields;
+
+    // `UnAuthorizedFields` is a plain `Vec<String>`, it is (de)serialized as a JSON array
+    // without any dedicated impl.
+    #[test]
+    fn unauthorized_fields_round_trip_as_json_array() {
+        let fields: UnAuthorizedFields = vec!["email".into(), "password".into()];
+
+        let json = serde_json::to_string(&fields).unwrap();
+        assert_eq!(json, r#"["email","password"]"#);
+
+        let parsed: UnAuthorizedFields = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, fields);
+    }
+
+    #[test]
+    fn scopes_are_serialized_as_canonical_strings() {
+        let scope = "write:user admin !guest".parse::<Scope>().unwrap();
+
+        let json = serde_json::to_string(&scope).unwrap();
+        assert_eq!(json, r#""admin write:user !guest""#);
+        assert_eq!(
+            serde_json::from_str::<String>(&json)
+                .unwrap()
+                .parse::<Scope>()
+                .unwrap(),
+            scope
+        );
+
+        let mut serializer = serde_json::Serializer::new(Vec::new());
+        scope_as_string(&scope, &mut serializer).unwrap();
+        assert_eq!(serializer.into_inner(), json.into_bytes());
+    }
+
+    #[test]
+    fn scope_requirements_are_serialized_as_scope_strings() {
+        let mut requirements = ScopeRequirements {
+            global_scope: Some("read:user".parse().unwrap()),
+            ..ScopeRequirements::default()
+        };
+        requirements
+            .field_scopes
+            .insert("iban".into(), "billing !guest".parse().unwrap());
+        requirements
+            .field_scopes
+            .insert("email".into(), "contact".parse().unwrap());
+
+        assert_eq!(
+            serde_json::to_string(&requirements).unwrap(),
+            r#"{"global_scope":"read:user","field_scopes":{"email":"contact","iban":"billing !guest"}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ScopeRequirements::default()).unwrap(),
+            r#"{"global_scope":null,"field_scopes":{}}"#
+        );
+    }
+
+    #[derive(::serde::Serialize)]
+    struct CustomerResponse {
+        id: MaybeAuthorized<i32>,
+        email: MaybeAuthorized<String>,
+        #[serde(skip_serializing_if = "MaybeAuthorized::is_unauthorized")]
+        iban: MaybeAuthorized<String>,
+    }
+
+    #[test]
+    fn unauthorized_fields_are_serialized_as_null() {
+        let unauthorized_fields: UnAuthorizedFields = vec!["email".into(), "iban".into()];
+        let response = CustomerResponse {
+            id: MaybeAuthorized::new(1, &unauthorized_fields, "id"),
+            email: MaybeAuthorized::new("email".into(), &unauthorized_fields, "email"),
+            iban: MaybeAuthorized::new("iban".into(), &unauthorized_fields, "iban"),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"id":1,"email":null}"#
+        );
+
+        let response = CustomerResponse {
+            id: Some(1).into(),
+            email: Some("email".to_string()).into(),
+            iban: Some("iban".to_string()).into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"id":1,"email":"email","iban":"iban"}"#
+        );
+
+        assert!(is_field_unauthorized(&"", &unauthorized_fields, "iban"));
+        assert!(!is_field_unauthorized(&1, &unauthorized_fields, "id"));
+    }
+
+    mod vec {
+        use crate::prelude::*;
+
+        #[derive(Debug, Authorized, ::serde::Serialize)]
+        struct Customer {
+            id: i32,
+            #[authorized(scope = "admin")]
+            email: String,
+        }
+
+        impl Authorized for Customer {}
+
+        #[test]
+        fn authorized_vecs_are_serialized_as_arrays() -> Result<(), AuthorizedError> {
+            let customers = vec![
+                Customer {
+                    id: 1,
+                    email: "first".into(),
+                },
+                Customer {
+                    id: 2,
+                    email: "second".into(),
+                },
+            ];
+
+            let result = Authorizor::authorize(&customers, &"user")?;
+            assert_eq!(
+                serde_json::to_string(&result).unwrap(),
+                r#"[{"id":1,"email":""},{"id":2,"email":""}]"#
+            );
+
+            let result = Authorizor::authorize(&customers, &"admin")?;
+            assert_eq!(
+                serde_json::to_string(&result).unwrap(),
+                r#"[{"id":1,"email":"first"},{"id":2,"email":"second"}]"#
+            );
+
+            Ok(())
+        }
+    }
+
+    fn result(status: AuthorizationStatus) -> AuthorizedResult<String> {
+        AuthorizedResult {
+            input_scope: "guest".parse::<Scope>().unwrap(),
+            inner: "name".into(),
+            status,
+            unauthorized_fields: vec!["email".into()],
+        }
+    }
+
+    #[test]
+    fn results_are_serialized_according_to_the_mode() {
+        let authorized = result(AuthorizationStatus::Authorized);
+        let unauthorized = result(AuthorizationStatus::UnAuthorized);
+        let to_json = |result: &AuthorizedResult<String>, mode| {
+            serde_json::to_string(&result.serialize_mode(mode)).unwrap()
+        };
+
+        assert_eq!(
+            to_json(&unauthorized, SerializationMode::default()),
+            r#""name""#
+        );
+        assert_eq!(
+            to_json(&authorized, SerializationMode::InnerOnly),
+            r#""name""#
+        );
+
+        assert_eq!(
+            to_json(&unauthorized, SerializationMode::WithStatus),
+            r#"{"data":"name","meta":{"input_scope":"guest","status":"UnAuthorized","unauthorized_fields":["email"]}}"#
+        );
+
+        assert_eq!(
+            to_json(&authorized, SerializationMode::NullIfUnauthorized),
+            r#""name""#
+        );
+        assert_eq!(
+            to_json(&unauthorized, SerializationMode::NullIfUnauthorized),
+            "null"
+        );
+    }
+
+    #[derive(Debug, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
+    struct Product {
+        id: i32,
+        name: String,
+    }
+
+    impl Authorized for Product {}
+
+    #[test]
+    fn results_are_deserialized_from_the_inner_format() {
+        let result: AuthorizedResult<Product> =
+            serde_json::from_str(r#"{"id": 1, "name": "test"}"#).unwrap();
+
+        assert_eq!(
+            result,
+            AuthorizedResult {
+                input_scope: "".parse::<Scope>().unwrap(),
+                inner: Product {
+                    id: 1,
+                    name: "test".into()
+                },
+                status: AuthorizationStatus::Authorized,
+                unauthorized_fields: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn results_are_deserialized_from_the_envelope_format() {
+        let result: AuthorizedResult<Product> = serde_json::from_str(
+            r#"{
+                "data": {"id": 1, "name": ""},
+                "meta": {
+                    "input_scope": "guest",
+                    "status": "UnAuthorized",
+                    "unauthorized_fields": ["name"]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(result.inner.id, 1);
+        assert_eq!(result.input_scope, "guest".parse::<Scope>().unwrap());
+        assert_eq!(result.status, AuthorizationStatus::UnAuthorized);
+        assert_eq!(result.unauthorized_fields, vec!["name".to_string()]);
+
+        assert!(serde_json::from_str::<AuthorizedResult<Product>>(
+            r#"{"data": {"id": 1, "name": ""}, "meta": {"input_scope": "\\"}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn statuses_are_serialized_as_their_name() {
+        let status = serde_json::to_string(&AuthorizationStatus::UnAuthorized).unwrap();
+        assert_eq!(status, r#""UnAuthorized""#);
+        assert_eq!(
+            serde_json::from_str::<AuthorizationStatus>(&status).unwrap(),
+            AuthorizationStatus::UnAuthorized
+        );
+
+        assert!(serde_json::from_str::<AuthorizationStatus>(r#""Denied""#).is_err());
+    }
+
+    #[test]
+    fn results_with_status_can_be_read_back() {
+        let unauthorized = AuthorizedResult {
+            input_scope: "guest !admin".parse::<Scope>().unwrap(),
+            inner: Product {
+                id: 1,
+                name: String::new(),
+            },
+            status: AuthorizationStatus::UnAuthorized,
+            unauthorized_fields: vec!["name".into()],
+        };
+
+        let json =
+            serde_json::to_string(&unauthorized.serialize_mode(SerializationMode::WithStatus))
+                .unwrap();
+        let read_back: AuthorizedResult<Product> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(read_back, unauthorized);
+    }
+}
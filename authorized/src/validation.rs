@@ -0,0 +1,53 @@
+//! Scope gated validation with the [`validator`](https://docs.rs/validator) crate.
+//!
+//! With the `with_validator` feature, `#[derive(Authorized)]` generates a
+//! `validate_authorized(&self, scope: &Scope)` method on structs with `#[validate(..)]` fields.
+//! It runs `Validate::validate` but drops the errors of the fields `scope` can't access: they
+//! are redacted by the authorization, their value doesn't matter.
+//!
+//! ```
+//! use authorized::prelude::*;
+//! use validator::Validate;
+//!
+//! #[derive(Authorized, Validate)]
+//! struct User {
+//!     #[validate(length(min = 1))]
+//!     name: String,
+//!     #[authorized(scope = "admin")]
+//!     #[validate(email)]
+//!     email: String,
+//! }
+//!
+//! impl Authorized for User {}
+//!
+//! let user = User { name: "name".into(), email: "".into() };
+//! let scope = "user".parse::<Scope>().unwrap();
+//!
+//! assert!(user.validate().is_err());
+//! assert!(user.validate_authorized(&scope).is_ok());
+//! ```
+
+pub use validator::{Validate, ValidationErrors};
+
+/// Keep the errors of the fields for which `is_authorized` returns `true`.
+///
+/// # Errors
+///
+/// Returns the remaining errors, if any.
+pub fn retain_authorized_errors<F: Fn(&str) -> bool>(
+    result: Result<(), ValidationErrors>,
+    is_authorized: F,
+) -> Result<(), ValidationErrors> {
+    let mut errors = match result {
+        Ok(()) => return Ok(()),
+        Err(errors) => errors,
+    };
+
+    errors.errors_mut().retain(|field, _| is_authorized(field));
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
@@ -0,0 +1,94 @@
+//! Validation of untrusted scopes.
+//!
+//! A scope coming from user input can contain any token. A
+//! [`ScopeWhitelist`](struct.ScopeWhitelist.html) rejects scopes granting tokens outside of a
+//! known list before they are used to authorize anything.
+
+use std::collections::HashSet;
+
+use crate::error::AuthorizedError;
+use crate::scope::Scope;
+
+/// Set of the tokens a scope is allowed to grant.
+///
+/// Only the allowed tokens of a scope are checked: denied tokens can only restrict access.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScopeWhitelist(pub HashSet<String>);
+
+impl ScopeWhitelist {
+    #[must_use]
+    pub fn new<I: IntoIterator<Item = S>, S: AsRef<str>>(tokens: I) -> Self {
+        Self(
+            tokens
+                .into_iter()
+                .map(|token| token.as_ref().to_string())
+                .collect(),
+        )
+    }
+
+    /// Checks that every token granted by `scope` is whitelisted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AuthorizedError::DisallowedScope` with the first token, in alphabetical order,
+    /// that isn't whitelisted.
+    pub fn validate(&self, scope: &Scope) -> Result<(), AuthorizedError> {
+        let mut disallowed: Vec<&str> = scope
+            .allowed_tokens()
+            .filter(|token| !self.0.contains(*token))
+            .collect();
+        disallowed.sort_unstable();
+
+        match disallowed.first() {
+            Some(token) => Err(AuthorizedError::DisallowedScope((*token).to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Clone, Authorized)]
+    struct Account {
+        id: i32,
+        #[authorized(scope = "admin")]
+        email: String,
+    }
+
+    impl Authorized for Account {}
+
+    #[test]
+    fn scopes_outside_of_the_whitelist_are_rejected() -> Result<(), AuthorizedError> {
+        let whitelist = ScopeWhitelist::new(["read:user", "user"]);
+
+        assert!(whitelist
+            .validate(&"user read:user !admin".parse()?)
+            .is_ok());
+        assert!(matches!(
+            whitelist.validate(&"user superuser admin".parse()?),
+            Err(AuthorizedError::DisallowedScope(token)) if token == "admin"
+        ));
+
+        let account = Account {
+            id: 1,
+            email: "email".into(),
+        };
+
+        let res = Authorizor::validate_and_authorize(&account, "user", &["user", "admin"])?;
+        assert_eq!(res.unauthorized_fields, vec!["email".to_string()]);
+
+        assert!(matches!(
+            Authorizor::validate_and_authorize(&account, "user admin", &["user"]),
+            Err(AuthorizedError::DisallowedScope(token)) if token == "admin"
+        ));
+        assert!(matches!(
+            Authorizor::validate_and_authorize(&account, "\"", &["user"]),
+            Err(AuthorizedError::ParseScopeError(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+//! The derived builder clones `Clone` structs once, and builds the other ones field by field.
+
+use authorized::prelude::*;
+
+/// Records if the struct was built by its own `Clone` impl.
+#[derive(Debug, Authorized)]
+struct Manual {
+    id: i32,
+    #[authorized(scope = "admin")]
+    email: String,
+    cloned_as_struct: bool,
+}
+
+impl Clone for Manual {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            email: self.email.clone(),
+            cloned_as_struct: true,
+        }
+    }
+}
+
+impl Authorized for Manual {}
+
+#[derive(Debug, Authorized)]
+/// `Clone` is derived in another attribute than `Authorized`.
+#[derive(Clone)]
+struct SeparateDerive {
+    #[authorized(scope = "admin")]
+    email: String,
+}
+
+impl Authorized for SeparateDerive {}
+
+#[derive(Debug, Authorized)]
+struct NotClone {
+    id: i32,
+    #[authorized(scope = "admin")]
+    email: String,
+}
+
+impl Authorized for NotClone {}
+
+#[test]
+fn clone_structs_are_cloned_once() -> Result<(), AuthorizedError> {
+    let manual = Manual {
+        id: 1,
+        email: "email".into(),
+        cloned_as_struct: false,
+    };
+
+    let result = Authorizor::authorize(&manual, &"user")?;
+    assert!(result.inner.cloned_as_struct);
+    assert_eq!(result.inner.id, 1);
+    assert!(result.inner.email.is_empty());
+
+    let derived = SeparateDerive {
+        email: "email".into(),
+    };
+
+    let result = Authorizor::authorize(&derived, &"admin")?;
+    assert_eq!(result.inner.email, "email");
+
+    Ok(())
+}
+
+#[test]
+fn other_structs_are_built_field_by_field() -> Result<(), AuthorizedError> {
+    let not_clone = NotClone {
+        id: 1,
+        email: "email".into(),
+    };
+
+    let result = Authorizor::authorize(&not_clone, &"user")?;
+    assert_eq!(result.inner.id, 1);
+    assert!(result.inner.email.is_empty());
+
+    let result = Authorizor::authorize(&not_clone, &"admin")?;
+    assert_eq!(result.inner.email, "email");
+
+    Ok(())
+}
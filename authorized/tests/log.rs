@@ -0,0 +1,71 @@
+//! The `log` records are global, so they are checked in their own test binary.
+#![cfg(feature = "with_log")]
+
+use std::sync::Mutex;
+
+use authorized::prelude::*;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+static RECORDS: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+
+struct Recorder;
+
+impl Log for Recorder {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "authorized::authorizor"
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[derive(Debug, Authorized)]
+#[authorized(scope = "admin")]
+struct Secret {
+    #[authorized(scope = "owner")]
+    value: String,
+}
+
+impl Authorized for Secret {}
+
+#[test]
+fn authorizations_are_logged() {
+    log::set_logger(&Recorder).unwrap();
+    log::set_max_level(LevelFilter::Debug);
+
+    let secret = Secret {
+        value: "value".into(),
+    };
+
+    Authorizor::authorize(&secret, &"admin").unwrap();
+    Authorizor::authorize(&secret, &"user").unwrap();
+    Authorizor::authorize(&secret, &"read:\"user\"").unwrap_err();
+
+    let records = RECORDS.lock().unwrap();
+    assert_eq!(
+        *records,
+        vec![
+            (
+                Level::Debug,
+                "scope: `admin`, status: Authorized, unauthorized fields: 1".to_string()
+            ),
+            (
+                Level::Debug,
+                "scope: `user`, status: UnAuthorized, unauthorized fields: 1".to_string()
+            ),
+            (Level::Warn, "scope `user` is unauthorized".to_string()),
+            (
+                Level::Error,
+                "authorization failed: Encountered invalid character in scope: \"".to_string()
+            ),
+        ]
+    );
+}
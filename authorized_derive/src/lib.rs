@@ -5,6 +5,7 @@
     clippy::nursery,
     // clippy::cargo
 )]
+#![allow(clippy::option_if_let_else)]
 #![recursion_limit = "256"]
 extern crate proc_macro;
 extern crate syn;
@@ -12,10 +13,14 @@ extern crate syn;
 extern crate quote;
 #[macro_use]
 extern crate darling;
+use std::collections::HashMap;
+
 use darling::ast;
-use darling::FromDeriveInput;
+use darling::util::SpannedValue;
+use darling::{FromDeriveInput, FromMeta};
 use proc_macro2::TokenStream;
 use quote::ToTokens;
+use syn::spanned::Spanned;
 
 #[derive(Debug, FromDeriveInput)]
 // This line says that we want to process all attributes declared with `my_trait`,
@@ -36,11 +41,15 @@ struct AuthorizedOpts {
     /// The Input Receiver demands a volume, so use `Volume::Normal` if the
     /// caller doesn't provide one.
     #[darling(default)]
-    scope: Option<String>,
+    scope: Option<SpannedValue<String>>,
+
+    /// The scope required by each group of fields, by group name.
+    #[darling(default)]
+    groups: HashMap<String, String>,
 }
 
-#[derive(Debug, FromField)]
-#[darling(attributes(authorized))]
+#[derive(Clone, Debug, FromField)]
+#[darling(attributes(authorized), forward_attrs(validate))]
 struct AuthorizedField {
     /// Get the ident of the field. For fields in tuple or newtype structs or
     /// enum bodies, this can be `None`.
@@ -49,15 +58,85 @@ struct AuthorizedField {
     /// This magic field name pulls the type from the input.
     ty: syn::Type,
 
+    /// The `validate` attributes of the field, from the `validator` crate.
+    #[allow(dead_code)]
     attrs: Vec<syn::Attribute>,
     /// We declare this as an `Option` so that during tokenization we can write
     /// `field.volume.unwrap_or(derive_input.volume)` to facilitate field-level
     /// overrides of struct-level settings.
     #[darling(default)]
-    scope: Option<String>,
+    scope: Option<SpannedValue<String>>,
+
+    #[darling(default)]
+    default: Option<SpannedValue<String>>,
+
+    /// Use `Default::default()` of the field type when unauthorized.
+    #[darling(default)]
+    or_default: bool,
+
+    /// Report the field as `authorized::SENSITIVE_FIELD` instead of its name in the unauthorized
+    /// fields returned to the caller.
+    #[darling(default)]
+    sensitive: bool,
 
+    /// The group of the field, whose scope is required along with the field scope.
     #[darling(default)]
-    default: Option<String>,
+    group: Option<SpannedValue<String>>,
+
+    /// The last `YYYY-MM-DD` day, UTC, on which the field can be authorized. From the next
+    /// midnight the field is unauthorized, whatever the scope.
+    #[darling(default)]
+    expiry: Option<SpannedValue<String>>,
+}
+
+impl AuthorizedField {
+    /// The expiry of the field as a Unix timestamp, if any. Invalid expiries are reported by
+    /// `generate_authorizable_trait`.
+    fn expiry_timestamp(&self) -> Option<u64> {
+        self.expiry
+            .as_ref()
+            .and_then(|expiry| expiry_to_unix_timestamp(expiry).ok())
+    }
+
+    /// The `default` function of the field, if any, spanned on the `default` attribute so type
+    /// errors point to it.
+    fn default_path(&self) -> Option<Result<syn::Path, darling::Error>> {
+        self.default.as_ref().map(|default| {
+            let lit = syn::LitStr::new(default, default.span());
+
+            lit.parse::<syn::Path>().map_err(|error| {
+                darling::Error::custom(format!("Invalid default path: {}", error)).with_span(&lit)
+            })
+        })
+    }
+}
+
+impl AuthorizedOpts {
+    /// Adds the scope of its group to the scope of `field`.
+    fn resolve_group(&self, field: &AuthorizedField) -> Result<AuthorizedField, syn::Error> {
+        let group = match &field.group {
+            Some(group) => group,
+            None => return Ok(field.clone()),
+        };
+        let group_scope = self.groups.get(group.as_str()).ok_or_else(|| {
+            syn::Error::new(
+                group.span(),
+                format!("Unknown scope group: {:?}", group.as_str()),
+            )
+        })?;
+
+        let scope = match &field.scope {
+            Some(scope) => {
+                SpannedValue::new(format!("{} {}", scope.as_str(), group_scope), scope.span())
+            }
+            None => SpannedValue::new(group_scope.clone(), group.span()),
+        };
+
+        Ok(AuthorizedField {
+            scope: Some(scope),
+            ..field.clone()
+        })
+    }
 }
 
 impl ToTokens for AuthorizedOpts {
@@ -69,9 +148,21 @@ impl ToTokens for AuthorizedOpts {
             .as_ref()
             .take_struct()
             .expect("Should never be enum")
-            .fields;
+            .fields
+            .into_iter()
+            .map(|field| self.resolve_group(field))
+            .collect::<Result<Vec<_>, _>>();
+        let fields = match fields {
+            Ok(fields) => fields,
+            Err(error) => return tokens.extend(error.to_compile_error()),
+        };
 
-        let authorizable_trait = generate_authorizable_trait(struct_name, &self.scope, &fields);
+        let authorizable_trait = generate_authorizable_trait(
+            struct_name,
+            &self.generics,
+            &self.scope,
+            &fields.iter().collect::<Vec<_>>(),
+        );
 
         tokens.extend(quote! {
             #authorizable_trait
@@ -79,45 +170,136 @@ impl ToTokens for AuthorizedOpts {
     }
 }
 
+/// An identifier for a local of the generated code, resolved at the macro definition site.
+fn local_ident(name: &str) -> syn::Ident {
+    syn::Ident::new(name, proc_macro2::Span::mixed_site())
+}
+
+/// Gives every token of `tokens` the location of `span`, so errors about them point to it.
+fn respan(tokens: TokenStream, span: proc_macro2::Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if let proc_macro2::TokenTree::Group(group) = &token {
+                let mut respanned =
+                    proc_macro2::Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                return respanned.into();
+            }
+            token.set_span(span.resolved_at(token.span()));
+            token
+        })
+        .collect()
+}
+
 fn generate_authorized_trait(
     _struct_name: &syn::Ident,
     fields: &[&AuthorizedField],
 ) -> proc_macro2::TokenStream {
-    let serialize_fields = fields
+    // Generated locals use `mixed_site` spans and prefixed names so they can't collide with the
+    // fields of the structure or with items of the calling crate.
+    let unauthorized_fields = local_ident("__authorized_unauthorized_fields");
+    let authorized = local_ident("__authorized_struct");
+
+    // Each field gets a closure building its default, shared by both ways of building the
+    // struct below.
+    let default_fields = fields
         .iter()
-        .enumerate()
-        .map(|(_i, f)| {
+        .map(|f| {
             let ident = if let Some(ref ident) = f.ident {
                 ident.clone()
             } else {
                 panic!("");
             };
 
-            let unauthorized = match &f.default {
+            let name = format!("{}", ident);
+            let ty = &f.ty;
+            let unauthorized = match f.default_path() {
+                None if f.or_default => quote! { <#ty as Default>::default() },
                 None => quote! { Default::default() },
-                Some(def) => match syn::parse_str::<syn::Path>(def) {
-                    Ok(path) => quote! { #path() },
-                    _ => panic!("Cannot parse default path"),
+                // The recover wrapper, and its `Default` fallback, is only used while recovering.
+                Some(Ok(path)) => quote! {
+                    if authorized::fallible::is_recovering() {
+                        use authorized::fallible::{PropagatePanic as _, RecoverWithDefault as _};
+                        (&authorized::fallible::FieldBuilder::new(#name, #path)).build()
+                    } else {
+                        #path()
+                    }
                 },
+                // Reported once by `generate_authorizable_trait`, which then skips this function.
+                Some(Err(_)) => quote! {},
             };
-
-            let name = format!("{}", ident);
-            let var_name = syn::Ident::new(&format!("arg_{}", name), ident.span());
+            let default_name = local_ident(&format!("__authorized_default_{}", name));
 
             quote! {
-                let #var_name = if !unauthorized_fields.iter().any(|v| v.as_ref() == #name) {
+                let #default_name = || -> #ty { #unauthorized };
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let is_unauthorized = |name: &str| {
+        quote! { #unauthorized_fields.iter().any(|v| v.as_ref() == #name) }
+    };
+
+    // A `Clone` struct is cloned once and only its unauthorized fields are overridden.
+    let override_fields = fields
+        .iter()
+        .filter_map(|f| {
+            let ident = f.ident.as_ref()?;
+            let name = ident.to_string();
+            let is_unauthorized = is_unauthorized(&name);
+            let default_name = local_ident(&format!("__authorized_default_{}", name));
+
+            Some(quote! {
+                if #is_unauthorized {
+                    #authorized.#ident = #default_name();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Otherwise each field is cloned or built on its own.
+    let serialize_fields = fields
+        .iter()
+        .filter_map(|f| {
+            let ident = f.ident.as_ref()?;
+            let name = ident.to_string();
+            let is_unauthorized = is_unauthorized(&name);
+            let default_name = local_ident(&format!("__authorized_default_{}", name));
+            let var_name = local_ident(&format!("__authorized_field_{}", name));
+
+            Some(quote! {
+                let #var_name = if !#is_unauthorized {
                     input.#ident.clone()
                 } else {
-                    #unauthorized
+                    #default_name()
                 };
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let clone_assertions = fields
+        .iter()
+        .map(|f| {
+            let ty = &f.ty;
+            // With a `default` function both checks are reported on the `default` attribute.
+            match f.default_path() {
+                Some(Ok(path)) => {
+                    let ty = respan(ty.to_token_stream(), path.span());
+                    quote_spanned! {path.span()=>
+                        authorized::assert_authorized_default::<#ty, _>(#path);
+                    }
+                }
+                _ => quote_spanned! {ty.span()=>
+                    authorized::assert_authorized_field::<#ty>();
+                },
             }
         })
         .collect::<Vec<_>>();
 
     let assign_field = fields
         .iter()
-        .enumerate()
-        .map(|(_i, f)| {
+        .map(|f| {
             let ident = if let Some(ref ident) = f.ident {
                 ident.clone()
             } else {
@@ -125,7 +307,7 @@ fn generate_authorized_trait(
             };
 
             let name = format!("{}", ident);
-            let var_name = syn::Ident::new(&format!("arg_{}", name), ident.span());
+            let var_name = local_ident(&format!("__authorized_field_{}", name));
 
             quote! {
                 #ident: #var_name
@@ -133,28 +315,49 @@ fn generate_authorized_trait(
         })
         .collect::<Vec<_>>();
 
+    // `StructBuilder` picks the first closure when the struct implements `Clone`, the second one
+    // otherwise.
+    let build = quote! {
+        #(#default_fields)*
+
+        #[allow(unused_imports)]
+        use authorized::{BuildFromFields as _, BuildWithClone as _};
+        Ok((&authorized::StructBuilder(input)).build(
+            |mut #authorized: Self| {
+                #(#override_fields)*
+                #authorized
+            },
+            || {
+                #(#serialize_fields)*
+                Self {
+                    #(#assign_field,)*
+                }
+            },
+        ))
+    };
+
     quote! {
+        #[doc = "Constructs the authorized version of this struct, replacing unauthorized fields with their defaults."]
         fn builder_authorized_struct<S: std::cmp::PartialEq + AsRef<str>>(input: &Self, unauthorized_fields: &[S]) -> Result<Self::Authorized, AuthorizedError>
         {
-            let unauthorized_fields = unauthorized_fields.as_ref();
-            #(#serialize_fields)*
+            #(#clone_assertions)*
 
-            Ok(Self::Authorized {
-                #(#assign_field,)*
-            })
+            let #unauthorized_fields = unauthorized_fields.as_ref();
+            #build
         }
     }
 }
 
 fn generate_authorizable_trait(
     struct_name: &syn::Ident,
-    global_scope: &Option<String>,
+    generics: &syn::Generics,
+    global_scope: &Option<SpannedValue<String>>,
     fields: &[&AuthorizedField],
 ) -> proc_macro2::TokenStream {
+    let struct_type = struct_name.to_string();
     let filtering_fields = fields
         .iter()
-        .enumerate()
-        .map(|(_i, f)| {
+        .map(|f| {
             let ident = if let Some(ref ident) = f.ident {
                 ident.clone()
             } else {
@@ -162,20 +365,194 @@ fn generate_authorizable_trait(
             };
 
             let name = format!("{}", ident);
-            if let Some(ref scope) = f.scope {
+            let access_granted = if let Some(ref scope) = f.scope {
+                let scope = scope.as_str();
                 quote! {
-                    if !#scope.parse::<authorized::scope::Scope>().unwrap().allow_access(scope) {
-                        unauthorized_fields.push(String::from(#name));
-                    }
+                    #scope.parse::<authorized::scope::Scope>().unwrap().allow_access(scope)
                 }
             } else {
-                quote! {}
+                quote! { true }
+            };
+            let access_granted = match f.expiry_timestamp() {
+                Some(expiry) => quote! {
+                    {
+                        const EXPIRY: u64 = #expiry;
+                        !authorized::is_expired(EXPIRY) && #access_granted
+                    }
+                },
+                None => access_granted,
+            };
+
+            let push_unauthorized = quote! {
+                if !access_granted {
+                    unauthorized_fields.push(String::from(#name));
+                }
+            };
+
+            quote! {
+                let access_granted = #access_granted;
+                field_audit.emit(#name, #struct_type, access_granted, scope);
+                #push_unauthorized
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Each field is checked once against its own scope, unlisted fields are authorized.
+    let mapped_filtering_fields = fields
+        .iter()
+        .zip(&filtering_fields)
+        .filter_map(|(f, filtering_field)| {
+            let name = f.ident.as_ref()?.to_string();
+
+            Some(quote! {
+                if let Some(scope) = field_scopes.get(#name) {
+                    #filtering_field
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let fetch_field_audit = if filtering_fields.is_empty() {
+        quote! {}
+    } else {
+        quote! { let field_audit = authorized::audit::FieldAudit::current(); }
+    };
+
+    let scope_errors = global_scope
+        .iter()
+        .chain(fields.iter().filter_map(|f| f.scope.as_ref()))
+        .filter_map(|scope| {
+            validate_scope_string(scope).err().map(|ch| {
+                syn::Error::new(
+                    scope.span(),
+                    format!("Invalid scope string: invalid character {:?}", ch),
+                )
+                .to_compile_error()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let expiry_errors = fields
+        .iter()
+        .filter_map(|f| f.expiry.as_ref())
+        .filter_map(|expiry| {
+            parse_date_to_unix_timestamp(expiry).err().map(|error| {
+                syn::Error::new(expiry.span(), format!("Invalid expiry: {}", error))
+                    .to_compile_error()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let default_errors = fields
+        .iter()
+        .filter_map(|f| f.default_path()?.err())
+        .map(darling::Error::write_errors)
+        .chain(fields.iter().filter(|f| f.or_default).filter_map(|f| {
+            f.default.as_ref().map(|default| {
+                syn::Error::new(
+                    default.span(),
+                    "`default` and `or_default` can't be used on the same field",
+                )
+                .to_compile_error()
+            })
+        }))
+        .collect::<Vec<_>>();
+
+    if !scope_errors.is_empty() || !expiry_errors.is_empty() || !default_errors.is_empty() {
+        return quote! { #(#scope_errors)* #(#expiry_errors)* #(#default_errors)* };
+    }
+
+    let (expiring_names, expiries): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .filter_map(|f| match (&f.ident, f.expiry_timestamp()) {
+            (Some(ident), Some(expiry)) => Some((ident.to_string(), expiry)),
+            _ => None,
+        })
+        .unzip();
+    let check_expiry = if expiring_names.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            let expired = match field {
+                #(#expiring_names => {
+                    const EXPIRY: u64 = #expiries;
+                    authorized::is_expired(EXPIRY)
+                })*
+                _ => false,
+            };
+            if expired {
+                return false;
+            }
+        }
+    };
+    let field_expiries = if expiring_names.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            #[doc = "Returns the fields of this struct with an expiry, as a Unix timestamp."]
+            fn field_expiries() -> &'static [(&'static str, u64)] {
+                &[#((#expiring_names, #expiries)),*]
             }
+        }
+    };
+
+    let field_scopes = fields
+        .iter()
+        .filter_map(|f| match (&f.ident, &f.scope) {
+            (Some(ident), Some(scope)) => {
+                let name = ident.to_string();
+                let scope = scope.as_str();
+                Some(quote! { #name => Some(#scope), })
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let field_names = fields
+        .iter()
+        .filter_map(|f| f.ident.as_ref().map(ToString::to_string))
+        .collect::<Vec<_>>();
+
+    let sensitive_field_names = fields
+        .iter()
+        .filter(|f| f.sensitive)
+        .filter_map(|f| f.ident.as_ref().map(ToString::to_string))
+        .collect::<Vec<_>>();
+    // The builder gets the real names, they're only masked in the returned result.
+    let result_unauthorized_fields = if sensitive_field_names.is_empty() {
+        quote! { unauthorized_fields }
+    } else {
+        quote! { unauthorized_fields: authorized::mask_sensitive_fields::<Self>(unauthorized_fields) }
+    };
+    let is_sensitive_field = if sensitive_field_names.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            #[doc = "Checks if the field is reported as `authorized::SENSITIVE_FIELD`."]
+            fn is_sensitive_field(field_name: &str) -> bool {
+                matches!(field_name, #(#sensitive_field_names)|*)
+            }
+        }
+    };
+
+    let scoped_field_names = fields
+        .iter()
+        .filter_map(|f| {
+            let name = f.ident.as_ref()?.to_string();
+
+            Some(match &f.scope {
+                Some(scope) => {
+                    let scope = scope.as_str();
+                    quote! { (#name, Some(#scope)) }
+                }
+                None => quote! { (#name, None) },
+            })
         })
         .collect::<Vec<_>>();
 
     let serialized_struct = generate_authorized_trait(struct_name, fields);
     let global_scopes = if let Some(gscope) = global_scope {
+        let gscope = gscope.as_str();
         quote! {
             let global_scopes: Vec<Scope> = vec!(#gscope.parse::<Scope>()?);
         }
@@ -185,15 +562,38 @@ fn generate_authorizable_trait(
         }
     };
 
+    let (required_names, required_scopes): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .filter_map(|f| match (&f.ident, &f.scope) {
+            (Some(ident), Some(scope)) => Some((ident.to_string(), scope.as_str())),
+            _ => None,
+        })
+        .unzip();
+    let required_global = match global_scope {
+        Some(scope) => {
+            let scope = scope.as_str();
+            quote! { Some(#scope.parse::<authorized::scope::Scope>().unwrap()) }
+        }
+        None => quote! { None },
+    };
+    let docs = authorization_docs(global_scope, fields);
+    // Lifetimes, type and const parameters are all forwarded to the impl.
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let validate_authorized = generate_validate_authorized(struct_name, generics, fields);
+
     quote! {
-        impl Authorizable for #struct_name {
-            type Authorized = #struct_name;
+        #(#[doc = #docs])*
+        impl #impl_generics Authorizable for #struct_name #ty_generics #where_clause {
+            #[doc = "The authorized version of this struct is the struct itself."]
+            type Authorized = Self;
 
             #serialized_struct
 
+            #[doc = "Returns the list of fields that are not accessible under the provided scope."]
             fn filter_unauthorized_fields(input: &Self, scope: &authorized::scope::Scope) -> UnAuthorizedFields
             {
-                let mut unauthorized_fields = vec![];
+                let mut unauthorized_fields: UnAuthorizedFields = vec![];
+                #fetch_field_audit
 
                 #(
                     #filtering_fields
@@ -202,6 +602,69 @@ fn generate_authorizable_trait(
                 unauthorized_fields
             }
 
+            #[doc = "Returns the list of fields that are not accessible under their own scope in `field_scopes`."]
+            fn filter_unauthorized_fields_with_map(input: &Self, field_scopes: &std::collections::HashMap<&str, authorized::scope::Scope>) -> UnAuthorizedFields
+            {
+                let mut unauthorized_fields: UnAuthorizedFields = vec![];
+                #fetch_field_audit
+
+                #(
+                    #mapped_filtering_fields
+                )*
+
+                unauthorized_fields
+            }
+
+            #[doc = "Returns the names of the fields of this struct, in declaration order."]
+            fn field_names() -> &'static [&'static str] {
+                &[#(#field_names),*]
+            }
+
+            #[doc = "Returns the names of the fields of this struct with their required scope, in declaration order."]
+            fn scoped_field_names() -> &'static [(&'static str, Option<&'static str>)] {
+                &[#(#scoped_field_names),*]
+            }
+
+            #[doc = "Returns the scope required to access the field, if any."]
+            fn field_scope(field_name: &str) -> Option<&'static str> {
+                match field_name {
+                    #(#field_scopes)*
+                    _ => None,
+                }
+            }
+
+            #is_sensitive_field
+
+            #field_expiries
+
+            #[doc = "Returns the scopes required by this struct and its fields."]
+            fn scope_requirements() -> authorized::ScopeRequirements {
+                let mut field_scopes = std::collections::HashMap::new();
+                #(
+                    field_scopes.insert(String::from(#required_names), #required_scopes.parse::<authorized::scope::Scope>().unwrap());
+                )*
+
+                authorized::ScopeRequirements {
+                    global_scope: #required_global,
+                    field_scopes,
+                }
+            }
+
+            #[doc = "Checks if the provided scope can access the field."]
+            fn is_authorized_for_field(_input: &Self, field: &str, scope: &authorized::scope::Scope) -> bool {
+                #check_expiry
+                Self::field_scope(field).map_or(true, |required| {
+                    required.parse::<authorized::scope::Scope>().unwrap().allow_access(scope)
+                })
+            }
+
+            #[doc = "Authorizes with each scope of the chain, only building the struct once."]
+            fn authorize_chain(input: &Self, scopes: &[authorized::scope::Scope]) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
+                #global_scopes
+                authorized::authorize_chain_with_global_scopes(input, scopes, &global_scopes)
+            }
+
+            #[doc = "Performs full authorization check and constructs the authorized result."]
             fn authorize(input: &Self, input_scope: &authorized::scope::Scope) -> Result<AuthorizedResult<Self::Authorized>, AuthorizedError> {
                 #global_scopes
                 let unauthorized_fields = Self::filter_unauthorized_fields(input, input_scope);
@@ -216,25 +679,498 @@ fn generate_authorizable_trait(
                     input_scope: input_scope.clone(),
                     inner,
                     status,
-                    unauthorized_fields
+                    #result_unauthorized_fields
                 })
             }
         }
 
+        #validate_authorized
+    }
+}
+
+/// Generates `validate_authorized` when a field has a `validate` attribute.
+#[cfg(feature = "with_validator")]
+fn generate_validate_authorized(
+    struct_name: &syn::Ident,
+    generics: &syn::Generics,
+    fields: &[&AuthorizedField],
+) -> TokenStream {
+    if fields.iter().all(|f| f.attrs.is_empty()) {
+        return quote! {};
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Validate the fields `scope` can access, the other fields are redacted by the
+            /// authorization so their validation errors are dropped.
+            pub fn validate_authorized(&self, scope: &authorized::scope::Scope) -> Result<(), authorized::validation::ValidationErrors> {
+                authorized::validation::retain_authorized_errors(
+                    authorized::validation::Validate::validate(self),
+                    |field| <Self as Authorizable>::is_authorized_for_field(self, field, scope),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "with_validator"))]
+fn generate_validate_authorized(
+    _struct_name: &syn::Ident,
+    _generics: &syn::Generics,
+    _fields: &[&AuthorizedField],
+) -> TokenStream {
+    quote! {}
+}
+
+/// The lines of the `# Authorization` documentation of the generated impl.
+fn authorization_docs(
+    global_scope: &Option<SpannedValue<String>>,
+    fields: &[&AuthorizedField],
+) -> Vec<String> {
+    let mut docs = vec![" # Authorization".to_string(), String::new()];
+
+    docs.push(match global_scope {
+        Some(scope) => format!(" Global scope: `{}`.", scope.as_str()),
+        None => " No global scope.".to_string(),
+    });
+    docs.push(String::new());
+
+    for field in fields {
+        let name = field
+            .ident
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let expiry = match &field.expiry {
+            Some(expiry) => format!(", expires after `{}`", expiry.as_str()),
+            None => String::new(),
+        };
+        let doc = match &field.scope {
+            None if field.expiry.is_none() => format!(" - `{}`: unrestricted", name),
+            scope => {
+                let redacted = match &field.default {
+                    Some(default) => format!("`{}()`", default.as_str()),
+                    None => "`Default::default()`".to_string(),
+                };
+                let scope = match scope {
+                    Some(scope) => format!("scope `{}`", scope.as_str()),
+                    None => "no scope".to_string(),
+                };
+                format!(
+                    " - `{}`: {}{}, redacted to {}{}",
+                    name,
+                    scope,
+                    expiry,
+                    redacted,
+                    if field.sensitive { ", sensitive" } else { "" }
+                )
+            }
+        };
+        docs.push(doc);
     }
+
+    docs
+}
+
+/// Mirrors `authorized::scope::ascii_validator::validate_scope_string`, which can't be used
+/// here as `authorized` depends on this crate.
+fn validate_scope_string(scope: &str) -> Result<(), char> {
+    match scope
+        .chars()
+        .find(|ch| !matches!(ch, ' ' | '\x21' | '\x23'..='\x5b' | '\x5d'..='\x7e'))
+    {
+        Some(ch) => Err(ch),
+        None => Ok(()),
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date into the Unix timestamp of its midnight, UTC.
+fn parse_date_to_unix_timestamp(date: &str) -> Result<u64, String> {
+    let invalid = || format!("expected a `YYYY-MM-DD` date from 1970, found {:?}", date);
+
+    let bytes = date.as_bytes();
+    let is_layout = bytes.len() == 10
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(i, byte)| i == 4 || i == 7 || byte.is_ascii_digit());
+    if !is_layout {
+        return Err(invalid());
+    }
+
+    let number = |range: std::ops::Range<usize>| date[range].parse::<u64>().map_err(|_| invalid());
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+
+    let is_leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if is_leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        _ => return Err(invalid()),
+    };
+    if year < 1970 || day == 0 || day > days_in_month {
+        return Err(invalid());
+    }
+
+    // Days since 0000-03-01, counting years from March so leap days end the year.
+    let days_from_civil = |year: u64, month: u64, day: u64| {
+        let (year, month) = if month <= 2 {
+            (year - 1, month + 9)
+        } else {
+            (year, month - 3)
+        };
+        year * 365 + year / 4 - year / 100 + year / 400 + (153 * month + 2) / 5 + day - 1
+    };
+
+    Ok((days_from_civil(year, month, day) - days_from_civil(1970, 1, 1)) * 86_400)
+}
+
+/// Parses an `expiry` date into the Unix timestamp of the following midnight, UTC, so the field
+/// stays authorized during the whole day.
+fn expiry_to_unix_timestamp(date: &str) -> Result<u64, String> {
+    parse_date_to_unix_timestamp(date).map(|midnight| midnight + 86_400)
+}
+
+/// Builds a `Scope` from a string literal validated at compile time.
+///
+/// ```ignore
+/// let scope = scope!("admin read:user !guest");
+/// ```
+#[proc_macro]
+pub fn scope(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let literal = match syn::parse::<syn::LitStr>(input) {
+        Ok(literal) => literal,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    let scope = literal.value();
+
+    if let Err(ch) = validate_scope_string(&scope) {
+        return syn::Error::new(
+            literal.span(),
+            format!("Encountered invalid character in scope: {}", ch),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    proc_macro::TokenStream::from(build_scope(&scope))
+}
+
+/// Builds the expression creating the `Scope` of an already validated scope string.
+fn build_scope(scope: &str) -> TokenStream {
+    let tokens = scope.split(' ').filter(|token| !token.is_empty());
+    let denied = tokens.clone().filter_map(|token| token.strip_prefix('!'));
+    let allowed = tokens.filter(|token| !token.starts_with('!'));
+
+    quote! {
+        authorized::scope::Scope::from_tokens(&[#(#allowed),*], &[#(#denied),*])
+    }
+}
+
+#[derive(Debug, FromMeta)]
+struct RouteOpts {
+    /// The scope required to call the function.
+    scope: SpannedValue<String>,
+}
+
+/// Checks if `ty` is `Scope` or `&Scope`.
+fn is_scope_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Reference(reference) => is_scope_type(&reference.elem),
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Scope"),
+        _ => false,
+    }
+}
+
+/// Requires a scope to call the annotated function.
+///
+/// The first argument of type `Scope` or `&Scope` is checked against the required scope before
+/// the body runs. If it doesn't give access, the function returns early with
+/// `AuthorizedError::InsufficientScope` converted with `Into` into its error type.
+///
+/// ```ignore
+/// #[authorize_route(scope = "admin")]
+/// fn delete_user(scope: &Scope, id: i32) -> Result<(), AuthorizedError> {
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn authorize_route(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    let function = syn::parse_macro_input!(item as syn::ItemFn);
+
+    let opts = match RouteOpts::from_list(&args) {
+        Ok(opts) => opts,
+        Err(error) => return error.write_errors().into(),
+    };
+
+    if let Err(ch) = validate_scope_string(opts.scope.as_str()) {
+        return syn::Error::new(
+            opts.scope.span(),
+            format!("Invalid scope string: invalid character {:?}", ch),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let scope_arg = function.sig.inputs.iter().find_map(|input| match input {
+        syn::FnArg::Typed(arg) if is_scope_type(&arg.ty) => match &*arg.pat {
+            syn::Pat::Ident(pat) => Some(pat.ident.clone()),
+            _ => None,
+        },
+        _ => None,
+    });
+
+    let scope_arg = match scope_arg {
+        Some(scope_arg) => scope_arg,
+        None => {
+            return syn::Error::new(
+                function.sig.span(),
+                "authorize_route requires an argument of type `Scope` or `&Scope`",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+
+    let required_scope = build_scope(opts.scope.as_str());
+    let syn::ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = function;
+
+    proc_macro::TokenStream::from(quote! {
+        #(#attrs)*
+        #vis #sig {
+            let __required_scope = #required_scope;
+            let __provided_scope: &authorized::scope::Scope =
+                ::std::borrow::Borrow::borrow(&#scope_arg);
+
+            if !__required_scope.allow_access(__provided_scope) {
+                return Err(::std::convert::From::from(
+                    authorized::prelude::AuthorizedError::InsufficientScope {
+                        required_fields: vec![],
+                        provided_scope: ::std::boxed::Box::new(__provided_scope.clone()),
+                    },
+                ));
+            }
+
+            #block
+        }
+    })
 }
 
 #[proc_macro_derive(Authorized, attributes(authorized))]
 pub fn derive_authorized(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    if let Ok(input) = syn::parse(input) {
-        // let input: DeriveInput = syn::parse(input).unwrap();
-        if let Ok(res) = AuthorizedOpts::from_derive_input(&input) {
-            proc_macro::TokenStream::from(quote!(#res))
-        } else {
-            proc_macro::TokenStream::new()
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match AuthorizedOpts::from_derive_input(&input) {
+        Ok(res) => proc_macro::TokenStream::from(quote!(#res)),
+        Err(error) => error.write_errors().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(input: &str) -> TokenStream {
+        let input = syn::parse_str::<syn::DeriveInput>(input).unwrap();
+
+        AuthorizedOpts::from_derive_input(&input)
+            .unwrap()
+            .to_token_stream()
+    }
+
+    /// The expansion of `input`, laid out by `prettyplease`.
+    fn expand_pretty(input: &str) -> String {
+        prettyplease::unparse(&syn::parse2::<syn::File>(expand(input)).unwrap())
+    }
+
+    /// Compares the expansion of `input` with the committed `src/snapshots` file of `name`.
+    ///
+    /// Run the tests with `INSTA_UPDATE=always`, or `cargo insta test`, to write the new
+    /// expansions, then review the snapshot changes before committing them.
+    fn assert_snapshot(name: &str, input: &str) {
+        insta::assert_snapshot!(name, expand_pretty(input));
+    }
+
+    #[test]
+    fn basic_struct_expansion() {
+        assert_snapshot(
+            "basic_struct",
+            r#"
+            struct Point {
+                x: i32,
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn field_scope_expansion() {
+        assert_snapshot(
+            "field_scope",
+            r#"
+            #[derive(Clone)]
+            #[authorized(scope = "read:user")]
+            struct User {
+                #[authorized(scope = "admin")]
+                email: String,
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn field_default_expansion() {
+        assert_snapshot(
+            "field_default",
+            r#"
+            struct Member {
+                #[authorized(scope = "admin", default = "lookup_nickname")]
+                nickname: String,
+                #[authorized(scope = "admin", or_default)]
+                visits: u32,
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn multiple_fields_expansion() {
+        assert_snapshot(
+            "multiple_fields",
+            r#"
+            #[derive(Debug, Clone)]
+            struct Citizen {
+                name: String,
+                #[authorized(scope = "contact")]
+                email: String,
+                #[authorized(scope = "tax !guest", sensitive)]
+                income: u32,
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn dates_are_parsed_to_unix_timestamps() {
+        assert_eq!(parse_date_to_unix_timestamp("1970-01-01"), Ok(0));
+        assert_eq!(parse_date_to_unix_timestamp("2000-03-01"), Ok(951_868_800));
+        assert_eq!(
+            parse_date_to_unix_timestamp("2024-02-29"),
+            Ok(1_709_164_800)
+        );
+        assert_eq!(
+            parse_date_to_unix_timestamp("2025-12-31"),
+            Ok(1_767_139_200)
+        );
+
+        for invalid in &[
+            "",
+            "2025-12",
+            "2025-13-01",
+            "2025-02-29",
+            "1969-12-31",
+            "2025-1-01",
+            "2025-01-01T00",
+            "+025-01-01",
+            "2025-1-011",
+            "2025-+1-01",
+        ] {
+            assert!(
+                parse_date_to_unix_timestamp(invalid).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn expiries_include_their_day() {
+        assert_eq!(expiry_to_unix_timestamp("1970-01-01"), Ok(86_400));
+        assert_eq!(expiry_to_unix_timestamp("2025-12-31"), Ok(1_767_225_600));
+        assert!(expiry_to_unix_timestamp("2025-02-29").is_err());
+    }
+
+    #[test]
+    fn const_generic_expansion() {
+        assert_snapshot(
+            "const_generic",
+            r#"
+            struct Buffer<const N: usize> {
+                data: Vec<u8>,
+                #[authorized(scope = "admin")]
+                checksum: u64,
+            }
+            "#,
+        );
+    }
+
+    #[test]
+    fn generated_methods_are_documented() {
+        let expanded = expand_pretty(
+            r#"
+            struct User {
+                #[authorized(scope = "admin")]
+                email: String,
+            }
+            "#,
+        );
+
+        let lines = expanded.lines().map(str::trim).collect::<Vec<_>>();
+        for (index, line) in lines.iter().enumerate().skip(1) {
+            if line.starts_with("fn ") || line.starts_with("type ") {
+                assert!(lines[index - 1].starts_with("///"), "{}", line);
+            }
+        }
+        assert!(expanded.contains("///Performs full authorization check"));
+    }
+
+    #[test]
+    fn generated_impl_documents_field_scopes() {
+        let expanded = expand(
+            r#"
+            #[authorized(scope = "read:user")]
+            struct User {
+                id: i32,
+                #[authorized(scope = "admin")]
+                email: String,
+                #[authorized(scope = "admin", default = "lookup_nickname")]
+                nickname: String,
+            }
+            "#,
+        )
+        .to_string();
+
+        for doc in &[
+            r#"# [doc = " # Authorization"]"#,
+            r#"# [doc = " Global scope: `read:user`."]"#,
+            r#"# [doc = " - `id`: unrestricted"]"#,
+            r#"# [doc = " - `email`: scope `admin`, redacted to `Default::default()`"]"#,
+            r#"# [doc = " - `nickname`: scope `admin`, redacted to `lookup_nickname()`"]"#,
+        ] {
+            assert!(
+                expanded.contains(doc),
+                "{:?} not found in {:?}",
+                doc,
+                expanded
+            );
         }
-    // let res = AuthorizedOpts::from_derive_input(&input).unwrap();
-    } else {
-        proc_macro::TokenStream::new()
     }
 }
@@ -0,0 +1,44 @@
+use actix_web::{web, App, HttpResponse, HttpServer};
+use authorized::actix::{ScopeGuard, ScopeMiddleware};
+use authorized::prelude::*;
+
+#[derive(Debug, Authorized)]
+struct User {
+    id: i32,
+    name: String,
+    #[authorized(scope = "admin")]
+    email: String,
+}
+
+impl Authorized for User {}
+
+async fn user(ScopeGuard(scope): ScopeGuard) -> HttpResponse {
+    let user = User {
+        id: 1,
+        name: "some_name".into(),
+        email: "some_email".into(),
+    };
+
+    match Authorizor::authorize(&user, &scope) {
+        Ok(result) => HttpResponse::Ok().body(format!("{:?}", result.inner)),
+        Err(_) => HttpResponse::InternalServerError().finish(),
+    }
+}
+
+// Try it with:
+//
+//   curl -H "Authorization: Bearer admin" http://127.0.0.1:8080/user
+//   curl http://127.0.0.1:8080/user
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let guest = "guest".parse::<Scope>().expect("valid scope");
+
+    HttpServer::new(move || {
+        App::new()
+            .wrap(ScopeMiddleware::with_fallback(guest.clone()))
+            .route("/user", web::get().to(user))
+    })
+    .bind(("127.0.0.1", 8080))?
+    .run()
+    .await
+}
@@ -32,5 +32,11 @@ fn main() -> Result<(), AuthorizedError> {
 
     println!("=> authorized without reader scope: {:?}", result);
 
+    let result = simple.into_authorized("reader")?;
+
+    assert_eq!(result.inner.name, "A simple struct");
+
+    println!("=> authorized with into_authorized: {:?}", result);
+
     Ok(())
 }
@@ -0,0 +1,18 @@
+#![no_main]
+
+use std::cmp::Ordering;
+
+use authorized::scope::Scope;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|scopes: (Scope, Scope)| {
+    let (lhs, rhs) = scopes;
+
+    assert_eq!(lhs.partial_cmp(&lhs), Some(Ordering::Equal));
+    assert!(lhs.allow_access(&lhs));
+    assert_eq!(
+        lhs.partial_cmp(&rhs).map(Ordering::reverse),
+        rhs.partial_cmp(&lhs)
+    );
+    assert_eq!(lhs.to_string().parse::<Scope>(), Ok(lhs));
+});